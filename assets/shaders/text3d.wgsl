@@ -4,60 +4,149 @@
 // The SDF generator lives in `src/font.rs` (see `Font::generate`) where a
 // grayscale SDF is written into the alpha channel of an RGBA image.
 
-#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_clip}
+#import bevy_pbr::{
+    mesh_functions::{get_tag, get_world_from_local, mesh_position_local_to_clip},
+    mesh_view_bindings::{globals, view},
+}
+#import "shaders/text3d_glyph.wgsl"::{
+    GlyphFragment, GlyphMaterialUniform, bevel_normal, bevel_shade, emission, glyph_color,
+    glyph_label, glyph_view, place_text, visibility_range_fade,
+}
+#ifdef GLYPH_INSTANCED
+#import "shaders/text3d_glyph.wgsl"::glyph_vertex
+#endif
+#ifdef GLYPH_XRAY
+#ifdef DEPTH_PREPASS
+#import bevy_pbr::prepass_utils::prepass_depth
+#endif
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+#import bevy_pbr::{
+    mesh_functions::get_visibility_range_dither_level,
+    pbr_functions::visibility_range_dither,
+}
+#endif
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
-    @location(0) position: vec2<f32>,
+#ifdef GLYPH_INSTANCED
+    @location(0) glyph_vertex: u32,
+#else
+    @location(0) position: vec3<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) color: vec4<f32>,
+    @location(3) glyph_coord: vec2<f32>,
+#ifdef GLYPH_EMISSIVE
+    @location(4) emissive: f32,
+#endif
+#endif
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) text_position: vec2<f32>,
+    @location(3) glyph_coord: vec2<f32>,
+    @location(4) emissive: f32,
+    @location(5) @interpolate(flat) opacity: f32,
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(6) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(7) @interpolate(flat) tint: vec4<f32>,
 };
 
 @vertex
 fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = mesh_position_local_to_clip(
-        get_world_from_local(vertex.instance_index),
-        vec4<f32>(vertex.position, 0.0, 1.0),
-    );
+#ifdef GLYPH_INSTANCED
+    let glyph = glyph_vertex(vertex.glyph_vertex);
+    let position = glyph.position;
+    out.uv = glyph.uv;
+    out.color = glyph.color;
+    out.text_position = glyph.text_position;
+    out.glyph_coord = glyph.glyph_coord;
+    out.emissive = glyph.emissive;
+#else
+    let position = vertex.position;
     out.uv = vertex.uv;
     out.color = vertex.color;
+    out.text_position = vertex.position.xy;
+    out.glyph_coord = vertex.glyph_coord;
+#ifdef GLYPH_EMISSIVE
+    out.emissive = vertex.emissive;
+#else
+    out.emissive = 0.0;
+#endif
+#endif
+#ifdef GLYPH_BATCHED
+    let label = glyph_label(glyph.label);
+#else
+    let label = glyph_label(get_tag(vertex.instance_index));
+#endif
+    out.tint = label.tint;
+    out.opacity = label.opacity;
+    // Billboards turn towards and screen sized text scales for the view being rendered, so
+    // each camera sees them facing it at their pixel height.
+    let world_from_text = place_text(
+        material_params,
+        get_world_from_local(vertex.instance_index) * label.world_from_label,
+        glyph_view(view.world_from_view, view.clip_from_view, view.viewport.w),
+    );
+    out.clip_position = mesh_position_local_to_clip(world_from_text, vec4<f32>(position, 1.0));
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither =
+        get_visibility_range_dither_level(vertex.instance_index, world_from_text[3]);
+#endif
     return out;
 }
 
-
-struct GlyphMaterialUniform {
-    params: vec4<f32>,
-};
-
 @group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> material_params: GlyphMaterialUniform;
 @group(#{MATERIAL_BIND_GROUP}) @binding(1) var material_sdf_texture: texture_2d<f32>;
 @group(#{MATERIAL_BIND_GROUP}) @binding(2) var material_sdf_sampler: sampler;
-
-// Convert a normalized SDF value to a smooth alpha using the pixel derivative.
-fn contour(d: f32, w: f32) -> f32 {
-    return smoothstep(0.5 - w, 0.5 + w, d);
-}
+@group(#{MATERIAL_BIND_GROUP}) @binding(5) var material_fill_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(6) var material_fill_sampler: sampler;
 
 @fragment
 fn fragment(
     mesh: VertexOutput,
 ) -> @location(0) vec4<f32> {
-    // Sample SDF stored in alpha channel. The generator writes distance in alpha.
-    let sample = textureSample(material_sdf_texture, material_sdf_sampler, mesh.uv);
-    let dist = sample.a;
-
-    // Derivative-aware smoothing: width is fwidth(dist) which adapts to
-    // transform/scale and provides good anti-aliasing in most cases.
-    let width = fwidth(dist) * material_params.params.x;
-    let alpha = contour(dist, width);
-
-    // Output glyph color with computed alpha.
-    return vec4(mesh.color.rgb, alpha);
-}
\ No newline at end of file
+    let glyph = GlyphFragment(mesh.uv, mesh.color, mesh.text_position, mesh.glyph_coord, mesh.tint, mesh.opacity);
+    let color = glyph_color(
+        material_params,
+        glyph,
+        material_sdf_texture,
+        material_sdf_sampler,
+        material_fill_texture,
+        material_fill_sampler,
+    );
+    let normal = bevel_normal(material_params, mesh.uv, material_sdf_texture, material_sdf_sampler);
+    let shade = bevel_shade(material_params, normal);
+    // Emission brightens the color past 1.0, which only HDR cameras keep.
+    let glow = 1.0 + emission(material_params, mesh.emissive, globals.time);
+    var out = vec4(color.rgb * shade * glow, color.a);
+#ifdef GLYPH_XRAY
+#ifdef DEPTH_PREPASS
+    // Drawn without depth testing; with reverse Z, surfaces in front have greater depth.
+    if mesh.clip_position.z < prepass_depth(mesh.clip_position, 0u) {
+        out.a *= material_params.occluded_opacity;
+    }
+#endif
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+#ifdef MAY_DISCARD
+    // Opaque glyphs can't fade, so they dissolve like other meshes leaving their range.
+    visibility_range_dither(mesh.clip_position, mesh.visibility_range_dither);
+#else
+    out.a *= visibility_range_fade(mesh.visibility_range_dither);
+#endif
+#endif
+#ifdef MAY_DISCARD
+    // Masked glyphs, and alpha to coverage without MSAA, are opaque inside the cutoff.
+    if out.a < material_params.alpha_cutoff {
+        discard;
+    }
+    out.a = 1.0;
+#endif
+    return out;
+}
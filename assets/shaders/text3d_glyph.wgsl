@@ -0,0 +1,418 @@
+// SDF glyph styling shared by the unlit and lit Text3d shaders.
+//
+// Textures and samplers are passed in because each material binds them at its own
+// indices. The label table is declared here at the index of `GlyphMaterial`, or of
+// `SdfGlyphExt` with `GLYPH_LIT`, and the glyph instances at the index of instanced
+// `GlyphMaterial`s, the only ones binding them.
+
+// Must match `MAX_GLYPH_OUTLINES` in `src/pipeline_material.rs`.
+const MAX_GLYPH_OUTLINES: u32 = 4u;
+
+struct GlyphMaterialUniform {
+    // x: smoothing, y: weight offset, z: opacity, w: outline count. The opacity is left at 1.0,
+    // as each label carries its own.
+    params: vec4<f32>,
+    // Color of each outline, innermost first.
+    outline_colors: array<vec4<f32>, MAX_GLYPH_OUTLINES>,
+    // Normalized SDF offset of the outer edge of each outline.
+    outline_edges: vec4<f32>,
+    // Glow and drop shadow colors; fully transparent disables them.
+    glow_color: vec4<f32>,
+    // x: SDF distance over which the glow fades out.
+    glow: vec4<f32>,
+    shadow_color: vec4<f32>,
+    // xy: shadow offset in atlas texels, z: SDF distance over which its edge fades out.
+    shadow: vec4<f32>,
+    // x: fill mode (0 vertex color, 1 linear gradient, 2 radial gradient, 3 image),
+    // y: fill space (0 text, 1 glyph).
+    fill: vec4<f32>,
+    fill_start_color: vec4<f32>,
+    fill_end_color: vec4<f32>,
+    // Linear: start and end points. Radial: center and radius. Image: UV scale and offset.
+    fill_geometry: vec4<f32>,
+    // x: SDF distance from the glyph edge to the bevel top (0 disables it),
+    // y: bevel height over its width.
+    bevel: vec4<f32>,
+    // xyz: text space direction towards the light shading unlit bevels.
+    bevel_light: vec4<f32>,
+    // x: emission as a multiple of the glyph color, y: pulse frequency in Hz,
+    // z: pulse depth, w: flicker depth.
+    emissive: vec4<f32>,
+    // x: on-screen pixels per text space unit (0 disables screen sizing),
+    // y: minimum and z: maximum world size of a text space unit (0 leaves it unbounded).
+    screen_size: vec4<f32>,
+    // Alpha below which masked glyphs are discarded.
+    alpha_cutoff: f32,
+    // 0: none, 1: spherical, 2: cylindrical, 3: screen aligned.
+    billboard: u32,
+    // Opacity of x-ray glyphs where other surfaces hide them.
+    occluded_opacity: f32,
+};
+
+// View the text is placed for by billboard and screen size styles.
+struct GlyphView {
+    position: vec3<f32>,
+    // World directions of the view's X, Y and Z axes; Z points back at the viewer.
+    right: vec3<f32>,
+    up: vec3<f32>,
+    back: vec3<f32>,
+    orthographic: bool,
+    // World size of a pixel one unit in front of perspective views, or anywhere in
+    // orthographic ones.
+    pixel_size: f32,
+};
+
+// Interpolated glyph attributes read by `glyph_color`.
+struct GlyphFragment {
+    uv: vec2<f32>,
+    color: vec4<f32>,
+    // Position in text space, before any label transform.
+    text_position: vec2<f32>,
+    // Position within the glyph quad, 0..1.
+    glyph_coord: vec2<f32>,
+    // Tint and opacity of the label the glyph belongs to.
+    tint: vec4<f32>,
+    opacity: f32,
+};
+
+// Must match `GlyphLabel` in `src/pipeline_material.rs`.
+struct GlyphLabel {
+    world_from_label: mat4x4<f32>,
+    previous_world_from_label: mat4x4<f32>,
+    tint: vec4<f32>,
+    opacity: f32,
+};
+
+#ifdef GLYPH_LIT
+@group(#{MATERIAL_BIND_GROUP}) @binding(105) var<storage, read> glyph_labels: array<GlyphLabel>;
+#else
+@group(#{MATERIAL_BIND_GROUP}) @binding(4) var<storage, read> glyph_labels: array<GlyphLabel>;
+#endif
+
+// Label `index` of the bound table: a label merged into the batch, indexed by its glyph
+// instances, or an unbatched `Text3d`, indexed by the `MeshTag` of its meshes. Unbatched
+// labels keep identity transforms, as their meshes are placed by their own; the batch
+// entity itself sits at the origin.
+fn glyph_label(index: u32) -> GlyphLabel {
+    return glyph_labels[index];
+}
+
+#ifdef GLYPH_INSTANCED
+// Must match `GlyphInstance` in `src/pipeline_material.rs`.
+struct GlyphInstance {
+    rect: vec4<f32>,
+    uv: vec4<f32>,
+    color: vec4<f32>,
+    linear: vec4<f32>,
+    z_offset: f32,
+    atlas: u32,
+    label: u32,
+    emissive: f32,
+};
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var<storage, read> glyph_instances: array<GlyphInstance>;
+
+struct GlyphVertex {
+    position: vec3<f32>,
+    uv: vec2<f32>,
+    color: vec4<f32>,
+    // Position in text space, before any label transform.
+    text_position: vec2<f32>,
+    glyph_coord: vec2<f32>,
+    emissive: f32,
+    // Index of the label the glyph belongs to, within a batch.
+    label: u32,
+};
+
+// Expands vertex `index` of the shared instanced mesh into a corner of its glyph quad.
+// Six vertices per glyph, in the same order as `Text3dBuilder::append_glyph`'s indices.
+fn glyph_vertex(index: u32) -> GlyphVertex {
+    var out: GlyphVertex;
+    out.label = 0u;
+    let glyph = index / 6u;
+    if glyph >= arrayLength(&glyph_instances) {
+        // Spare vertices of the shared mesh collapse onto the origin.
+        out.position = vec3<f32>(0.0);
+        out.uv = vec2<f32>(0.0);
+        out.color = vec4<f32>(0.0);
+        out.text_position = vec2<f32>(0.0);
+        out.glyph_coord = vec2<f32>(0.0);
+        out.emissive = 0.0;
+        return out;
+    }
+    let instance = glyph_instances[glyph];
+    var corners = array<u32, 6>(0u, 1u, 3u, 1u, 2u, 3u);
+    let corner = corners[index % 6u];
+    // Corners run min, (max.x, min.y), max, (min.x, max.y).
+    let use_max = vec2<bool>(corner == 1u || corner == 2u, corner >= 2u);
+    let point = select(instance.rect.xy, instance.rect.zw, use_max);
+    let center = (instance.rect.xy + instance.rect.zw) * 0.5;
+    let linear = mat2x2<f32>(instance.linear.xy, instance.linear.zw);
+    out.position = vec3<f32>(center + linear * (point - center), instance.z_offset);
+    out.text_position = out.position.xy;
+    out.glyph_coord = select(vec2<f32>(0.0), vec2<f32>(1.0), use_max);
+    out.label = instance.label;
+    out.uv = select(instance.uv.xy, instance.uv.zw, use_max);
+    out.color = instance.color;
+    out.emissive = instance.emissive;
+    return out;
+}
+#endif
+
+// Convert a normalized SDF value to a smooth alpha using the pixel derivative.
+fn contour(d: f32, w: f32) -> f32 {
+    return smoothstep(0.5 - w, 0.5 + w, d);
+}
+
+// Composites straight-alpha `top` over `bottom`.
+fn blend_over(top: vec4<f32>, bottom: vec4<f32>) -> vec4<f32> {
+    let alpha = top.a + bottom.a * (1.0 - top.a);
+    if alpha <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    let rgb = (top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a)) / alpha;
+    return vec4<f32>(rgb, alpha);
+}
+
+// Color of the fill at the fragment, multiplied with the glyph color.
+fn fill_color(
+    params: GlyphMaterialUniform,
+    glyph: GlyphFragment,
+    fill_texture: texture_2d<f32>,
+    fill_sampler: sampler,
+) -> vec4<f32> {
+    let coord = select(glyph.text_position, glyph.glyph_coord, params.fill.y > 0.5);
+    let geometry = params.fill_geometry;
+    var t = 0.0;
+    switch u32(params.fill.x) {
+        case 1u: {
+            let direction = geometry.zw - geometry.xy;
+            t = dot(coord - geometry.xy, direction) / max(dot(direction, direction), 1e-8);
+        }
+        case 2u: {
+            t = length(coord - geometry.xy) / max(geometry.z, 1e-8);
+        }
+        case 3u: {
+            // Images run top-down while text space points up.
+            let uv = vec2(coord.x, 1.0 - coord.y) * geometry.xy + geometry.zw;
+            return textureSample(fill_texture, fill_sampler, uv);
+        }
+        default: {
+            return vec4<f32>(1.0);
+        }
+    }
+    return mix(params.fill_start_color, params.fill_end_color, saturate(t));
+}
+
+// Straight-alpha color of the styled glyph at the fragment: its fill, outlines, glow and
+// drop shadow, faded by the style and label opacity.
+fn glyph_color(
+    params: GlyphMaterialUniform,
+    glyph: GlyphFragment,
+    sdf_texture: texture_2d<f32>,
+    sdf_sampler: sampler,
+    fill_texture: texture_2d<f32>,
+    fill_sampler: sampler,
+) -> vec4<f32> {
+    // Sample SDF stored in alpha channel. The generator writes distance in alpha.
+    let sample = textureSample(sdf_texture, sdf_sampler, glyph.uv);
+    let dist = sample.a;
+
+    // Derivative-aware smoothing: width is fwidth(dist) which adapts to
+    // transform/scale and provides good anti-aliasing in most cases.
+    let width = fwidth(dist) * params.params.x;
+    let fill = fill_color(params, glyph, fill_texture, fill_sampler) * glyph.color * glyph.tint;
+    // A positive weight moves the edge outwards, making glyphs bolder.
+    var color = vec4(fill.rgb, fill.a * contour(dist + params.params.y, width));
+
+    // Each outline fills the glyph grown to its outer edge and is drawn behind the
+    // fill and the outlines inside it.
+    // Glow and shadow start from the outer edge of the glyph and its outlines.
+    var outer_edge = params.params.y;
+    let outline_count = min(u32(params.params.w), MAX_GLYPH_OUTLINES);
+    for (var i = 0u; i < outline_count; i++) {
+        let outline = params.outline_colors[i];
+        outer_edge = params.outline_edges[i];
+        let coverage = contour(dist + outer_edge, width);
+        color = blend_over(color, vec4(outline.rgb, outline.a * coverage));
+    }
+
+    let glow_color = params.glow_color;
+    if glow_color.a > 0.0 {
+        let radius = params.glow.x;
+        let glow = smoothstep(0.5 - radius - width, 0.5 + width, dist + outer_edge);
+        color = blend_over(color, vec4(glow_color.rgb, glow_color.a * glow));
+    }
+
+    let shadow_color = params.shadow_color;
+    if shadow_color.a > 0.0 {
+        // Atlas UVs run along the same axes as the text, so the offset maps directly.
+        let texel = 1.0 / vec2<f32>(textureDimensions(sdf_texture));
+        let shadow_uv = glyph.uv - params.shadow.xy * texel;
+        let shadow_dist = textureSample(sdf_texture, sdf_sampler, shadow_uv).a;
+        let softness = params.shadow.z;
+        let shadow = smoothstep(0.5 - softness - width, 0.5 + width, shadow_dist + outer_edge);
+        color = blend_over(color, vec4(shadow_color.rgb, shadow_color.a * shadow));
+    }
+
+    return vec4(color.rgb, color.a * params.params.z * glyph.opacity);
+}
+
+// Normal of the bevelled glyph surface in text space, tilted along the SDF gradient on the
+// inside of the glyph edge. Faces +Z outside the bevel and when the style has none.
+fn bevel_normal(
+    params: GlyphMaterialUniform,
+    uv: vec2<f32>,
+    sdf_texture: texture_2d<f32>,
+    sdf_sampler: sampler,
+) -> vec3<f32> {
+    let width = params.bevel.x;
+    if width <= 0.0 {
+        return vec3<f32>(0.0, 0.0, 1.0);
+    }
+    // Central differences one texel apart; atlas UVs run along the text axes.
+    let texel = 1.0 / vec2<f32>(textureDimensions(sdf_texture));
+    let dist = textureSample(sdf_texture, sdf_sampler, uv).a;
+    let right = textureSample(sdf_texture, sdf_sampler, uv + vec2(texel.x, 0.0)).a;
+    let left = textureSample(sdf_texture, sdf_sampler, uv - vec2(texel.x, 0.0)).a;
+    let up = textureSample(sdf_texture, sdf_sampler, uv + vec2(0.0, texel.y)).a;
+    let down = textureSample(sdf_texture, sdf_sampler, uv - vec2(0.0, texel.y)).a;
+    let gradient = vec2(right - left, up - down);
+
+    let inside = (dist + params.params.y - 0.5) / width;
+    if inside <= 0.0 || inside >= 1.0 || dot(gradient, gradient) < 1e-12 {
+        return vec3<f32>(0.0, 0.0, 1.0);
+    }
+    // Rounded profile, steepest at the edge and flat at the top. The SDF increases inwards,
+    // so the surface leans away from its gradient.
+    let slope = params.bevel.y * 2.0 * (1.0 - inside);
+    return normalize(vec3(-slope * normalize(gradient), 1.0));
+}
+
+// Brightness of a bevel `normal` under the style's light, relative to a flat glyph.
+fn bevel_shade(params: GlyphMaterialUniform, normal: vec3<f32>) -> f32 {
+    let light = params.bevel_light.xyz;
+    return max(dot(normal, light), 0.0) / max(light.z, 0.1);
+}
+
+// Light emitted at `time` as a multiple of the glyph color, from the style's and the glyph's
+// own emission, dimmed by the style's pulse and flicker.
+fn emission(params: GlyphMaterialUniform, glyph_emissive: f32, time: f32) -> f32 {
+    let emissive = params.emissive;
+    // Eases between full emission and `1 - depth` of it.
+    let pulse = 1.0 - emissive.z * (0.5 - 0.5 * cos(6.2831855 * emissive.y * time));
+    // A new random dip 20 times a second.
+    let flicker = 1.0 - emissive.w * fract(sin(floor(time * 20.0) * 12.9898) * 43758.5453);
+    return (emissive.x + glyph_emissive) * pulse * flicker;
+}
+
+// Opacity of text fading across its `VisibilityRange`, from the dither level Bevy computes
+// for it: 1 inside the range, falling to 0 where it's culled.
+fn visibility_range_fade(dither: i32) -> f32 {
+    return 1.0 - f32(min(abs(dither), 16)) / 16.0;
+}
+
+// Describes the view with the given transforms and viewport height in pixels.
+fn glyph_view(
+    world_from_view: mat4x4<f32>,
+    clip_from_view: mat4x4<f32>,
+    viewport_height: f32,
+) -> GlyphView {
+    return GlyphView(
+        world_from_view[3].xyz,
+        normalize(world_from_view[0].xyz),
+        normalize(world_from_view[1].xyz),
+        normalize(world_from_view[2].xyz),
+        clip_from_view[3].w == 1.0,
+        // Clip space spans 2 units vertically.
+        2.0 / (clip_from_view[1].y * max(viewport_height, 1.0)),
+    );
+}
+
+// Places the text of `world_from_text` for `view`: turned by the style's billboard, then
+// sized by its screen size.
+fn place_text(
+    params: GlyphMaterialUniform,
+    world_from_text: mat4x4<f32>,
+    view: GlyphView,
+) -> mat4x4<f32> {
+    return screen_size(params, billboard(params, world_from_text, view), view);
+}
+
+// Turns `world_from_text` about its origin to face `view` as the style's billboard mode asks,
+// keeping its scale. Returns it unchanged for styles without a billboard.
+fn billboard(
+    params: GlyphMaterialUniform,
+    world_from_text: mat4x4<f32>,
+    view: GlyphView,
+) -> mat4x4<f32> {
+    let mode = params.billboard;
+    if mode == 0u {
+        return world_from_text;
+    }
+    let origin = world_from_text[3].xyz;
+    let scale = vec3(
+        length(world_from_text[0].xyz),
+        length(world_from_text[1].xyz),
+        length(world_from_text[2].xyz),
+    );
+    // Text faces +Z, so its Z axis points at the viewer, or against the view direction
+    // of orthographic views.
+    let to_view = view.position - origin;
+    var back = select(normalize(to_view), view.back, view.orthographic || dot(to_view, to_view) < 1e-12);
+    var right = view.right;
+    var up = view.up;
+    switch mode {
+        case 1u: {
+            // Upright, unless seen from straight above or below.
+            let level = cross(vec3<f32>(0.0, 1.0, 0.0), back);
+            right = select(right - back * dot(right, back), level, dot(level, level) > 1e-6);
+            right = normalize(right);
+            up = cross(back, right);
+        }
+        case 2u: {
+            // Turns about the world Y axis only.
+            let level = vec3(back.x, 0.0, back.z);
+            back = select(vec3<f32>(0.0, 0.0, 1.0), normalize(level), dot(level, level) > 1e-12);
+            up = vec3<f32>(0.0, 1.0, 0.0);
+            right = cross(up, back);
+        }
+        default: {
+            back = view.back;
+        }
+    }
+    return mat4x4<f32>(
+        vec4(right * scale.x, 0.0),
+        vec4(up * scale.y, 0.0),
+        vec4(back * scale.z, 0.0),
+        world_from_text[3],
+    );
+}
+
+// Scales `world_from_text` about its origin so a text space unit covers the style's pixel
+// height in `view`, within its world size bounds. Returns it unchanged without screen sizing.
+fn screen_size(
+    params: GlyphMaterialUniform,
+    world_from_text: mat4x4<f32>,
+    view: GlyphView,
+) -> mat4x4<f32> {
+    let size = params.screen_size;
+    if size.x <= 0.0 {
+        return world_from_text;
+    }
+    let origin = world_from_text[3].xyz;
+    // Pixels grow with the distance along the view direction in perspective views.
+    let depth = select(max(dot(view.position - origin, view.back), 1e-4), 1.0, view.orthographic);
+    var height = max(size.x * view.pixel_size * depth, size.y);
+    if size.z > 0.0 {
+        height = min(height, size.z);
+    }
+    // Uniform scale keeps the text's aspect ratio.
+    let scale = height / max(length(world_from_text[1].xyz), 1e-8);
+    return mat4x4<f32>(
+        world_from_text[0] * scale,
+        world_from_text[1] * scale,
+        world_from_text[2] * scale,
+        world_from_text[3],
+    );
+}
@@ -0,0 +1,167 @@
+// Lit SDF shader used by `LitGlyphMaterial`.
+//
+// Coverage and style come from the same atlas sampling as `text3d.wgsl`; the styled color
+// then stands in for the `StandardMaterial` base color and goes through Bevy's PBR lighting,
+// so the glyphs receive lights, shadows and distance fog like any other surface.
+
+#import bevy_pbr::{
+    mesh_bindings::mesh,
+    mesh_functions::{get_tag, get_world_from_local, mesh_position_local_to_world},
+    mesh_view_bindings::{globals, view},
+    pbr_bindings,
+    pbr_functions::{
+        alpha_discard, apply_pbr_lighting, calculate_view, main_pass_post_lighting_processing,
+        prepare_world_normal,
+    },
+    pbr_types::pbr_input_new,
+    view_transformations::position_world_to_clip,
+}
+#import "shaders/text3d_glyph.wgsl"::{
+    GlyphFragment, GlyphMaterialUniform, bevel_normal, emission, glyph_color, glyph_label,
+    glyph_view, place_text, visibility_range_fade,
+}
+#ifdef GLYPH_XRAY
+#ifdef DEPTH_PREPASS
+#import bevy_pbr::prepass_utils::prepass_depth
+#endif
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+#import bevy_pbr::{
+    mesh_functions::get_visibility_range_dither_level,
+    pbr_functions::visibility_range_dither,
+}
+#endif
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) glyph_coord: vec2<f32>,
+#ifdef GLYPH_EMISSIVE
+    @location(4) emissive: f32,
+#endif
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) color: vec4<f32>,
+    @location(4) text_position: vec2<f32>,
+    @location(5) glyph_coord: vec2<f32>,
+    @location(6) @interpolate(flat) instance_index: u32,
+    @location(7) emissive: f32,
+    // World directions of the text X and Y axes.
+    @location(8) world_tangent: vec3<f32>,
+    @location(9) world_bitangent: vec3<f32>,
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(10) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(11) @interpolate(flat) tint: vec4<f32>,
+    @location(12) @interpolate(flat) opacity: f32,
+};
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> glyph_params: GlyphMaterialUniform;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var glyph_sdf_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var glyph_sdf_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103) var glyph_fill_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104) var glyph_fill_sampler: sampler;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    let world_from_local = place_text(
+        glyph_params,
+        get_world_from_local(vertex.instance_index),
+        glyph_view(view.world_from_view, view.clip_from_view, view.viewport.w),
+    );
+    out.world_position = mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+    out.clip_position = position_world_to_clip(out.world_position.xyz);
+    out.world_tangent = normalize(world_from_local[0].xyz);
+    out.world_bitangent = normalize(world_from_local[1].xyz);
+    // Glyph quads lie in the text's XY plane, facing +Z.
+    out.world_normal = normalize(cross(world_from_local[0].xyz, world_from_local[1].xyz));
+    out.uv = vertex.uv;
+    out.color = vertex.color;
+    out.text_position = vertex.position.xy;
+    out.glyph_coord = vertex.glyph_coord;
+    out.instance_index = vertex.instance_index;
+    let label = glyph_label(get_tag(vertex.instance_index));
+    out.tint = label.tint;
+    out.opacity = label.opacity;
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither =
+        get_visibility_range_dither_level(vertex.instance_index, world_from_local[3]);
+#endif
+#ifdef GLYPH_EMISSIVE
+    out.emissive = vertex.emissive;
+#else
+    out.emissive = 0.0;
+#endif
+    return out;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> @location(0) vec4<f32> {
+    let glyph = GlyphFragment(
+        in.uv,
+        in.color,
+        in.text_position,
+        in.glyph_coord,
+        in.tint,
+        in.opacity,
+    );
+    let color = glyph_color(
+        glyph_params,
+        glyph,
+        glyph_sdf_texture,
+        glyph_sdf_sampler,
+        glyph_fill_texture,
+        glyph_fill_sampler,
+    );
+
+    var pbr_input = pbr_input_new();
+    pbr_input.material = pbr_bindings::material;
+    var base_color = pbr_input.material.base_color * color;
+#ifdef GLYPH_XRAY
+#ifdef DEPTH_PREPASS
+    // Drawn without depth testing; with reverse Z, surfaces in front have greater depth.
+    if in.clip_position.z < prepass_depth(in.clip_position, 0u) {
+        base_color.a *= glyph_params.occluded_opacity;
+    }
+#endif
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+#ifdef MAY_DISCARD
+    // Opaque glyphs can't fade, so they dissolve like other meshes leaving their range.
+    visibility_range_dither(in.clip_position, in.visibility_range_dither);
+#else
+    base_color.a *= visibility_range_fade(in.visibility_range_dither);
+#endif
+#endif
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, base_color);
+    // Emitted light keeps the glyph color, whatever the lights around it.
+    let glow = emission(glyph_params, in.emissive, globals.time);
+    pbr_input.material.emissive += vec4(color.rgb * glow, 0.0);
+    pbr_input.flags = mesh[in.instance_index].flags;
+    pbr_input.frag_coord = in.clip_position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.is_orthographic = view.clip_from_view[3].w == 1.0;
+    pbr_input.V = calculate_view(in.world_position, pbr_input.is_orthographic);
+    // Text is readable from both sides, so the back face is lit along the flipped normal.
+    pbr_input.world_normal = prepare_world_normal(in.world_normal, true, is_front);
+    // The bevel leans along the text axes, which point the same way seen from either side.
+    let bevel = bevel_normal(glyph_params, in.uv, glyph_sdf_texture, glyph_sdf_sampler);
+    pbr_input.N = normalize(
+        in.world_tangent * bevel.x + in.world_bitangent * bevel.y
+            + normalize(pbr_input.world_normal) * bevel.z
+    );
+
+    let lit = apply_pbr_lighting(pbr_input);
+    return main_pass_post_lighting_processing(pbr_input, lit);
+}
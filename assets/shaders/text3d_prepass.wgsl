@@ -1,36 +1,283 @@
-// Prepass shader for Text3d material - no material bindings needed.
+// Prepass and deferred shader shared by `GlyphMaterial` and `LitGlyphMaterial`.
+//
+// Fragments outside the glyph coverage are discarded, so depth, shadows, normals and motion
+// vectors follow the glyph outlines instead of their quads. `GLYPH_LIT` selects the bindings of
+// `SdfGlyphExt` and the `StandardMaterial` it extends.
 
-#import bevy_pbr::mesh_functions::{get_world_from_local, mesh_position_local_to_clip}
+#import bevy_pbr::{
+    mesh_functions::{get_previous_world_from_local, get_tag, get_world_from_local},
+    mesh_view_bindings::view,
+    prepass_bindings::previous_view_uniforms,
+    view_transformations::position_world_to_clip,
+}
+#import bevy_render::globals::Globals
+#import "shaders/text3d_glyph.wgsl"::{
+    GlyphFragment, GlyphMaterialUniform, GlyphView, bevel_normal, bevel_shade, emission,
+    glyph_color, glyph_label, glyph_view, place_text,
+}
+#ifdef GLYPH_INSTANCED
+#import "shaders/text3d_glyph.wgsl"::glyph_vertex
+#endif
+#ifdef PREPASS_FRAGMENT
+#import bevy_pbr::prepass_io::FragmentOutput
+#endif
+#ifdef DEFERRED_PREPASS
+#import bevy_pbr::{
+    mesh_bindings::mesh,
+    pbr_deferred_functions::deferred_gbuffer_from_pbr_input,
+    pbr_types::{pbr_input_new, STANDARD_MATERIAL_FLAGS_UNLIT_BIT},
+}
+#endif
+#ifdef GLYPH_LIT
+#import bevy_pbr::pbr_bindings
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+#import bevy_pbr::{
+    mesh_functions::get_visibility_range_dither_level,
+    pbr_functions::visibility_range_dither,
+}
+#endif
+
+// Prepass views bind the globals next to the view, not at their main pass index.
+@group(0) @binding(1) var<uniform> globals: Globals;
 
 struct Vertex {
     @builtin(instance_index) instance_index: u32,
-    @location(0) position: vec2<f32>,
+#ifdef GLYPH_INSTANCED
+    @location(0) glyph_vertex: u32,
+#else
+    @location(0) position: vec3<f32>,
     @location(1) uv: vec2<f32>,
     @location(2) color: vec4<f32>,
+    @location(3) glyph_coord: vec2<f32>,
+#ifdef GLYPH_EMISSIVE
+    @location(4) emissive: f32,
+#endif
+#endif
 };
 
-// Prepass vertex output - includes UV for fragment shader
-struct PrepassVertexOutput {
-    @builtin(position) clip_position: vec4<f32>,
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
     @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) text_position: vec2<f32>,
+    @location(3) glyph_coord: vec2<f32>,
+    @location(4) emissive: f32,
+    @location(5) world_position: vec4<f32>,
+    // World directions of the text X and Y axes and of the glyph face.
+    @location(6) world_tangent: vec3<f32>,
+    @location(7) world_bitangent: vec3<f32>,
+    @location(8) world_normal: vec3<f32>,
+    @location(9) @interpolate(flat) instance_index: u32,
+#ifdef MOTION_VECTOR_PREPASS
+    @location(10) previous_world_position: vec4<f32>,
+#endif
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    @location(11) unclipped_depth: f32,
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    @location(12) @interpolate(flat) visibility_range_dither: i32,
+#endif
+    @location(13) @interpolate(flat) opacity: f32,
+    @location(14) @interpolate(flat) tint: vec4<f32>,
 };
 
-// Prepass vertex shader - outputs position and UV
-@vertex
-fn vertex(vertex: Vertex) -> PrepassVertexOutput {
-    var out: PrepassVertexOutput;
-    out.clip_position = mesh_position_local_to_clip(
-        get_world_from_local(vertex.instance_index),
-        vec4<f32>(vertex.position, 0.0, 1.0),
+#ifdef MOTION_VECTOR_PREPASS
+// The previous frame's view, which billboards faced and screen sized text was scaled for then.
+fn previous_glyph_view() -> GlyphView {
+    // View transforms are rigid, so the inverse rotation is the transpose.
+    let view_from_world = previous_view_uniforms.view_from_world;
+    let rotation = transpose(mat3x3<f32>(
+        view_from_world[0].xyz,
+        view_from_world[1].xyz,
+        view_from_world[2].xyz,
+    ));
+    let world_from_view = mat4x4<f32>(
+        vec4(rotation[0], 0.0),
+        vec4(rotation[1], 0.0),
+        vec4(rotation[2], 0.0),
+        vec4(-(rotation * view_from_world[3].xyz), 1.0),
     );
+    // The viewport size of the previous frame isn't kept.
+    return glyph_view(world_from_view, previous_view_uniforms.clip_from_view, view.viewport.w);
+}
+#endif
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+#ifdef GLYPH_INSTANCED
+    let glyph = glyph_vertex(vertex.glyph_vertex);
+    let position = glyph.position;
+    out.uv = glyph.uv;
+    out.color = glyph.color;
+    out.text_position = glyph.text_position;
+    out.glyph_coord = glyph.glyph_coord;
+    out.emissive = glyph.emissive;
+#else
+    let position = vertex.position;
     out.uv = vertex.uv;
+    out.color = vertex.color;
+    out.text_position = vertex.position.xy;
+    out.glyph_coord = vertex.glyph_coord;
+#ifdef GLYPH_EMISSIVE
+    out.emissive = vertex.emissive;
+#else
+    out.emissive = 0.0;
+#endif
+#endif
+#ifdef GLYPH_BATCHED
+    let label = glyph_label(glyph.label);
+#else
+    let label = glyph_label(get_tag(vertex.instance_index));
+#endif
+    out.tint = label.tint;
+    out.opacity = label.opacity;
+    // Billboards turn towards the view being rendered: the camera in its depth prepass, and
+    // the light in shadow maps, so their shadows keep the full glyph shapes. Screen sized text
+    // casts no shadows, as its size only holds for the camera.
+    let world_from_text = place_text(
+        material_params,
+        get_world_from_local(vertex.instance_index) * label.world_from_label,
+        glyph_view(view.world_from_view, view.clip_from_view, view.viewport.w),
+    );
+    out.world_position = world_from_text * vec4<f32>(position, 1.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.unclipped_depth = out.position.z;
+    out.position.z = min(out.position.z, 1.0);
+#endif
+    out.world_tangent = normalize(world_from_text[0].xyz);
+    out.world_bitangent = normalize(world_from_text[1].xyz);
+    // Glyph quads lie in the text's XY plane, facing +Z.
+    out.world_normal = normalize(cross(world_from_text[0].xyz, world_from_text[1].xyz));
+    out.instance_index = vertex.instance_index;
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither =
+        get_visibility_range_dither_level(vertex.instance_index, world_from_text[3]);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    // Batched labels keep their transforms of the previous frame next to the current ones.
+    let previous_world_from_text = place_text(
+        material_params,
+        get_previous_world_from_local(vertex.instance_index) * label.previous_world_from_label,
+        previous_glyph_view(),
+    );
+    out.previous_world_position = previous_world_from_text * vec4<f32>(position, 1.0);
+#endif
     return out;
 }
 
-// Prepass fragment shader - write depth for all pixels (approximation for text shadows)
+#ifdef GLYPH_LIT
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> material_params: GlyphMaterialUniform;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var material_sdf_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var material_sdf_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103) var material_fill_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104) var material_fill_sampler: sampler;
+#else
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> material_params: GlyphMaterialUniform;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var material_sdf_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var material_sdf_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(5) var material_fill_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(6) var material_fill_sampler: sampler;
+#endif
+
+// Styled glyph color at the fragment, as drawn by the main pass before lighting.
+fn prepass_color(in: VertexOutput) -> vec4<f32> {
+    let glyph = GlyphFragment(in.uv, in.color, in.text_position, in.glyph_coord, in.tint, in.opacity);
+    let color = glyph_color(
+        material_params,
+        glyph,
+        material_sdf_texture,
+        material_sdf_sampler,
+        material_fill_texture,
+        material_fill_sampler,
+    );
+#ifdef GLYPH_LIT
+    return pbr_bindings::material.base_color * color;
+#else
+    return color;
+#endif
+}
+
+// Discards fragments the main pass would not draw opaque. Blended glyphs only reach the
+// prepass when casting shadows, which stop at the same cutoff. Text leaving its visibility
+// range is dithered like other meshes, whatever its alpha mode.
+fn prepass_discard(in: VertexOutput, color: vec4<f32>) {
+#ifdef VISIBILITY_RANGE_DITHER
+    visibility_range_dither(in.position, in.visibility_range_dither);
+#endif
+#ifdef MAY_DISCARD
+    if color.a < material_params.alpha_cutoff {
+        discard;
+    }
+#endif
+}
+
+#ifdef PREPASS_FRAGMENT
 @fragment
 fn fragment(
-    mesh: PrepassVertexOutput,
-) -> @location(0) vec4<f32> {
-    return vec4(0.0, 0.0, 0.0, 1.0);
-}
\ No newline at end of file
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let color = prepass_color(in);
+    // Sampled before any discard, while the quad's derivatives are still defined.
+    let bevel = bevel_normal(material_params, in.uv, material_sdf_texture, material_sdf_sampler);
+    prepass_discard(in, color);
+
+    var out: FragmentOutput;
+
+    // Text is readable from both sides, so the back face points the other way, while the
+    // bevel leans along the text axes whichever side it is seen from.
+    let face_normal = select(-in.world_normal, in.world_normal, is_front);
+    let normal = normalize(
+        in.world_tangent * bevel.x + in.world_bitangent * bevel.y + face_normal * bevel.z
+    );
+
+#ifdef NORMAL_PREPASS
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+
+#ifdef MOTION_VECTOR_PREPASS
+    let clip_position_t = view.unjittered_clip_from_world * in.world_position;
+    let clip_position = clip_position_t.xy / clip_position_t.w;
+    let previous_clip_position_t = previous_view_uniforms.clip_from_world * in.previous_world_position;
+    let previous_clip_position = previous_clip_position_t.xy / previous_clip_position_t.w;
+    // Same encoding as Bevy's prepass: UV offsets, with V pointing down.
+    out.motion_vector = (clip_position - previous_clip_position) * vec2(0.5, -0.5);
+#endif
+
+#ifdef DEFERRED_PREPASS
+    var pbr_input = pbr_input_new();
+    let glow = emission(material_params, in.emissive, globals.time);
+#ifdef GLYPH_LIT
+    pbr_input.material = pbr_bindings::material;
+    pbr_input.material.base_color = vec4(color.rgb, 1.0);
+    pbr_input.material.emissive += vec4(color.rgb * glow, 0.0);
+#else
+    // Unlit glyphs go through the gbuffer's emissive channel, shaded as in the main pass.
+    let shade = bevel_shade(material_params, bevel);
+    pbr_input.material.flags |= STANDARD_MATERIAL_FLAGS_UNLIT_BIT;
+    pbr_input.material.base_color = vec4(color.rgb * shade * (1.0 + glow), 1.0);
+#endif
+    pbr_input.flags = mesh[in.instance_index].flags;
+    pbr_input.frag_coord = in.position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = face_normal;
+    pbr_input.N = normal;
+    out.deferred = deferred_gbuffer_from_pbr_input(pbr_input);
+    out.deferred_lighting_pass_id = pbr_input.material.deferred_lighting_pass_id;
+#endif
+
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.frag_depth = in.unclipped_depth;
+#endif
+
+    return out;
+}
+#else
+@fragment
+fn fragment(in: VertexOutput) {
+    prepass_discard(in, prepass_color(in));
+}
+#endif
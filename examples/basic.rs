@@ -100,11 +100,7 @@ fn spawn_text_when_loaded(
                 let pos = bevy::math::Rect::from_corners(text_cursor, text_cursor + info.size);
                 min_corner = min_corner.min(pos.min);
                 max_corner = max_corner.max(pos.max);
-                glyphs.push(Glyph {
-                    position: pos,
-                    character: c,
-                    color: [1.0, 1.0, 1.0, 1.0],
-                });
+                glyphs.push(Glyph::from_cursor(pos, c, [1.0, 1.0, 1.0, 1.0]));
                 // TODO: handle kerning properly
                 text_cursor.x += info.advance.x + 0.02; // gap between characters
             }
@@ -121,13 +117,10 @@ fn spawn_text_when_loaded(
             }
         }
 
-        // Spawn the Text3d mesh with glyphs and request atlas generation for the used code points
+        // Spawn the Text3d mesh with glyphs; `set_glyphs` requests atlas generation
+        // for the used code points.
         let mut text_mesh = Text3d::new(font_handle.0.clone());
         text_mesh.set_glyphs(glyphs.into_boxed_slice());
-        // Atlases already generated above, so no need to request missing
-        // codepoints here. But keep the call in case fonts change later.
-        let codepoints: Vec<char> = text.chars().collect();
-        text_mesh.add_missing(&codepoints);
 
         let text_mesh_scale = 1.0;
 
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy_text3d::{
+    Font, Glyph, GlyphProfileRenderMode, Text3d, Text3dBatched, Text3dPlugin, Text3dStyle,
+};
+
+/// Number of nameplates along each side of the grid.
+const GRID_SIZE: i32 = 70;
+const SPACING: f32 = 2.5;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
+enum AppState {
+    #[default]
+    Loading,
+    Ready,
+}
+
+fn main() {
+    let mut app = App::new();
+
+    app.add_plugins(DefaultPlugins)
+        .add_plugins(Text3dPlugin)
+        .init_state::<AppState>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            check_font_loaded.run_if(in_state(AppState::Loading)),
+        )
+        .add_systems(OnEnter(AppState::Ready), spawn_nameplates)
+        .add_systems(Update, bob_nameplates.run_if(in_state(AppState::Ready)))
+        .run();
+}
+
+#[derive(Resource)]
+struct FontHandle(Handle<Font>);
+
+/// A nameplate that bobs up and down, moving without changing its glyphs.
+#[derive(Component)]
+struct Nameplate {
+    base: Vec3,
+    phase: f32,
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(FontHandle(asset_server.load("fonts/FiraCode-Bold.ttf")));
+
+    let extent = GRID_SIZE as f32 * SPACING * 0.5;
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, extent, extent * 1.5).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+fn check_font_loaded(
+    fonts: Res<Assets<Font>>,
+    font_handle: Res<FontHandle>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if fonts.get(&font_handle.0).is_some() {
+        next_state.set(AppState::Ready);
+    }
+}
+
+/// Lays out `text` on a single line centered on the origin.
+fn layout(font: &Font, text: &str) -> Box<[Glyph]> {
+    let mut cursor = Vec2::ZERO;
+    let mut glyphs = Vec::new();
+    for c in text.chars() {
+        if let Some(info) = font.glyph(c) {
+            let pos = Rect::from_corners(cursor, cursor + info.size);
+            glyphs.push(Glyph::from_cursor(pos, c, [1.0, 0.9, 0.4, 1.0]));
+            cursor.x += info.advance.x;
+        }
+    }
+    let offset = Vec2::new(cursor.x * 0.5, 0.0);
+    for glyph in glyphs.iter_mut() {
+        glyph.position =
+            Rect::from_corners(glyph.position.min - offset, glyph.position.max - offset);
+    }
+    glyphs.into_boxed_slice()
+}
+
+fn spawn_nameplates(
+    mut commands: Commands,
+    fonts: Res<Assets<Font>>,
+    font_handle: Res<FontHandle>,
+) {
+    let Some(font) = fonts.get(&font_handle.0) else {
+        return;
+    };
+
+    let half = GRID_SIZE as f32 * SPACING * 0.5;
+    for x in 0..GRID_SIZE {
+        for z in 0..GRID_SIZE {
+            let mut text_mesh = Text3d::new(font_handle.0.clone())
+                .with_glyph_profile_mode(GlyphProfileRenderMode::None);
+            text_mesh.set_glyphs(layout(font, &format!("Unit {:04}", x * GRID_SIZE + z)));
+
+            let base = Vec3::new(x as f32 * SPACING - half, 0.0, z as f32 * SPACING - half);
+            commands.spawn((
+                text_mesh,
+                // All nameplates share one batch per atlas page instead of owning their meshes.
+                Text3dBatched,
+                // A dark outline keeps the names readable over each other.
+                Text3dStyle::default().with_outline(1.5, Color::BLACK),
+                Nameplate {
+                    base,
+                    phase: (x + z) as f32 * 0.3,
+                },
+                Transform::from_translation(base),
+            ));
+        }
+    }
+}
+
+fn bob_nameplates(time: Res<Time>, mut query: Query<(&Nameplate, &mut Transform)>) {
+    for (nameplate, mut transform) in query.iter_mut() {
+        let height = (time.elapsed_secs() * 2.0 + nameplate.phase).sin() * 0.3;
+        transform.translation = nameplate.base + Vec3::Y * height;
+    }
+}
@@ -21,7 +21,7 @@ use std::collections::BTreeSet;
 use bevy::{asset::AssetId, image::Image, prelude::*};
 use bevy_log::info;
 use bevy_text3d::{
-    Font, FontAtlasSets, Glyph, Text3d, Text3dPlugin,
+    Font, FontAtlasSets, Glyph, GlyphTransform, Text3d, Text3dPlugin,
     dump_glyph_profile::dump_glyph_profile_obj_on_key,
 };
 // open_space_controller removed for standalone repository — use default Bevy camera instead
@@ -561,11 +561,15 @@ fn animate_text_effects(
                     let min = Vec2::new(pos_x + metrics.offset.x, pos_y + metrics.offset.y);
                     let pos = bevy::math::Rect::from_corners(min, min + metrics.size);
                     let color_phase = ((elapsed * 0.8) + i as f32 * 0.5).sin() * 0.5 + 0.5;
-                    glyphs.push(Glyph::from_rect(
-                        pos,
-                        *c,
-                        [0.2 + 0.4 * color_phase, 0.7, 1.0, 1.0],
-                    ));
+                    // Keep each glyph upright relative to the circle it travels on
+                    let transform = GlyphTransform {
+                        rotation: angle - std::f32::consts::FRAC_PI_2,
+                        ..Default::default()
+                    };
+                    glyphs.push(
+                        Glyph::from_rect(pos, *c, [0.2 + 0.4 * color_phase, 0.7, 1.0, 1.0])
+                            .with_transform(transform),
+                    );
                 }
             }
         }
@@ -49,6 +49,7 @@ fn main() {
             text_mesh_config: TextMeshPluginConfig {
                 text_mesh_shadow_quality: GlyphTessellationQuality::High,
                 font_scale: Vec3::ONE,
+                ..Default::default()
             },
         })
         .init_state::<AppState>()
@@ -140,11 +141,7 @@ fn spawn_text_when_loaded(
         for c in text.chars() {
             if let Some(info) = font.glyph(c) {
                 let pos = bevy::math::Rect::from_corners(text_cursor, text_cursor + info.size);
-                glyphs.push(Glyph {
-                    position: pos,
-                    character: c,
-                    color: [1.0, 1.0, 1.0, 1.0],
-                });
+                glyphs.push(Glyph::from_cursor(pos, c, [1.0, 1.0, 1.0, 1.0]));
                 text_cursor.x += info.advance.x + 0.02; // small gap
             }
         }
@@ -119,6 +119,7 @@ fn main() {
             text_mesh_config: TextMeshPluginConfig {
                 text_mesh_shadow_quality: GlyphTessellationQuality::High, // Reasonably smooth shadow outlines
                 font_scale: Vec3::ONE,
+                ..Default::default()
             },
         })
         .insert_resource(CurrentShadowQuality(ShadowQuality::High))
@@ -294,11 +295,7 @@ fn spawn_text_when_loaded(
             if let Some(info) = font.glyph(c) {
                 // Use the glyph offset so the quad aligns with the glyph's bounding box
                 let pos = bevy::math::Rect::from_corners(text_cursor, text_cursor + info.size);
-                glyphs.push(Glyph {
-                    position: pos,
-                    character: c,
-                    color: [1.0, 1.0, 1.0, 1.0],
-                });
+                glyphs.push(Glyph::from_cursor(pos, c, [1.0, 1.0, 1.0, 1.0]));
                 // TODO: handle kerning properly
                 text_cursor.x += info.advance.x + 0.02; // gap between characters
             }
@@ -455,7 +452,6 @@ fn handle_quality_input(
 fn update_shadow_quality(
     current_quality: Res<CurrentShadowQuality>,
     mut config: ResMut<TextMeshPluginConfig>,
-    mut text_query: Query<&mut Text3d>,
     mut previous_quality: Local<ShadowQuality>,
 ) {
     debug!(
@@ -464,16 +460,12 @@ fn update_shadow_quality(
     );
     if *previous_quality != current_quality.0 {
         debug!(
-            "Quality changed from {:?} to {:?}, updating config and clearing glyph profiles",
+            "Quality changed from {:?} to {:?}, updating config",
             *previous_quality, current_quality.0
         );
         *previous_quality = current_quality.0;
+        // Text3dPlugin rebuilds the glyph profiles of every Text3d when the config changes.
         config.text_mesh_shadow_quality = current_quality.0.to_glyph_quality();
         info!("Updated shadow quality to {:?}", current_quality.0);
-
-        // Clear glyph profile meshes so they get recreated with new quality
-        for mut text3d in text_query.iter_mut() {
-            text3d.clear_glyph_profile();
-        }
     }
 }
@@ -35,6 +35,7 @@ fn main() {
             text_mesh_config: TextMeshPluginConfig {
                 text_mesh_shadow_quality: GlyphTessellationQuality::High,
                 font_scale: Vec3::ONE,
+                ..Default::default()
             },
         })
         .init_state::<AppState>()
@@ -169,11 +170,7 @@ fn spawn_text(mut commands: Commands, fonts: Res<Assets<Font>>, font_handle: Res
     for c in text.chars() {
         if let Some(info) = font.glyph(c) {
             let pos = bevy::math::Rect::from_corners(text_cursor, text_cursor + info.size);
-            glyphs.push(Glyph {
-                position: pos,
-                character: c,
-                color: [0.0, 0.0, 1.0, 1.0], // Blue text
-            });
+            glyphs.push(Glyph::from_cursor(pos, c, [0.0, 0.0, 1.0, 1.0])); // Blue text
             text_cursor.x += info.advance.x + 0.02;
         }
     }
@@ -0,0 +1,580 @@
+//! Opt-in batching of many [`Text3d`] labels into a few shared draws.
+//!
+//! Every batched label using the same font atlas page and style is merged into a single instanced
+//! mesh. Glyph records are stored in text space together with the index of their label, and
+//! each label's world transform and opacity live in a separate table, so moving or fading a
+//! label only rewrites its record in that table while changing its glyphs rebuilds the records
+//! of the batches it touches.
+
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+use bevy::{
+    asset::{AssetId, Assets, Handle},
+    camera::{primitives::Aabb, visibility::NoFrustumCulling},
+    light::NotShadowCaster,
+    math::{Mat4, Vec3, Vec4},
+    prelude::{
+        Added, Changed, Commands, Component, DetectChanges, DetectChangesMut, Entity,
+        GlobalTransform, Has, InheritedVisibility, Mesh, Mesh3d, MeshMaterial3d, Or, ParamSet,
+        Query, Ref, RemovedComponents, Res, ResMut, Resource, Transform, Visibility,
+    },
+    render::storage::ShaderStorageBuffer,
+};
+use bevy_log::{debug, info};
+
+use crate::{
+    font::{Font, FontAtlasSets},
+    pipeline::{
+        GlyphInstanceMeshes, PositionedGlyph, Text3d, TextMeshConfigOverride, TextMeshPluginConfig,
+        billboard_bounds, quad_corners,
+    },
+    pipeline_material::{
+        GlyphInstance, GlyphLabel, GlyphMaterial, GlyphRecordWrites, upload_glyph_records,
+    },
+    style::{StyleKey, Text3dStyle},
+};
+
+/// Draws a [`Text3d`] as part of a shared batch instead of with its own meshes and child
+/// entities.
+///
+/// All batched labels using the same font atlas page and [`Text3dStyle`] are merged into one
+/// draw, which suits thousands of small labels such as unit nameplates. Labels whose styles
+/// only differ in `opacity` and `tint` share a batch. A batch is culled as
+/// a whole rather than per label. Glyph profile meshes for shadows are still created per label.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Text3dBatched;
+
+/// Labels sharing a font atlas page and style, up to their opacity and tint, share a batch.
+type BatchKey = (AssetId<Font>, usize, StyleKey);
+
+/// The batches of all [`Text3dBatched`] labels.
+#[derive(Default, Resource)]
+pub struct Text3dBatches {
+    batches: HashMap<BatchKey, TextBatch>,
+}
+
+struct TextBatch {
+    entity: Entity,
+    material: Handle<GlyphMaterial>,
+    glyph_instances: Handle<ShaderStorageBuffer>,
+    label_records: Handle<ShaderStorageBuffer>,
+    mesh: Handle<Mesh>,
+    labels: HashMap<Entity, BatchLabel>,
+    // Culling bounds of every label, rebuilt with the glyph records and grown as labels move
+    aabb: Aabb,
+    // Set when labels were added, removed or changed their glyphs
+    glyphs_dirty: bool,
+    // Labels that moved, faded or changed their tint since their record was last written
+    dirty_labels: HashSet<Entity>,
+}
+
+struct BatchLabel {
+    glyphs: Vec<GlyphInstance>,
+    // Bounds of the glyphs in label space
+    bounds: Aabb,
+    transform: Mat4,
+    // Transform uploaded in the previous frame, for motion vectors
+    previous_transform: Mat4,
+    opacity: f32,
+    tint: Vec4,
+    // Index of the label's transform, assigned when the glyph records are rebuilt
+    slot: u32,
+}
+
+impl BatchLabel {
+    /// Returns the label's record, moving from its transform of the previous upload.
+    fn record(&self) -> GlyphLabel {
+        GlyphLabel {
+            world_from_label: self.transform,
+            previous_world_from_label: self.previous_transform,
+            tint: self.tint,
+            opacity: self.opacity,
+        }
+    }
+
+    /// Returns the corners of the label's bounds in world space.
+    fn world_corners(&self) -> [Vec3; 8] {
+        let (min, max) = (Vec3::from(self.bounds.min()), Vec3::from(self.bounds.max()));
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+        .map(|corner| self.transform.transform_point3(corner))
+    }
+}
+
+impl Text3dBatches {
+    /// Removes `label` from every batch.
+    fn remove_label(&mut self, label: Entity) {
+        for batch in self.batches.values_mut() {
+            if batch.labels.remove(&label).is_some() {
+                batch.glyphs_dirty = true;
+            }
+        }
+    }
+}
+
+/// System that adds, updates and removes batched [`Text3d`] labels and uploads the batches
+/// they changed.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_text_batches_system(
+    mut text_queries: ParamSet<(
+        Query<
+            (
+                Entity,
+                &mut Text3d,
+                Ref<GlobalTransform>,
+                Ref<InheritedVisibility>,
+                Ref<Text3dBatched>,
+                Option<Ref<Text3dStyle>>,
+                Option<&TextMeshConfigOverride>,
+            ),
+            Or<(
+                Changed<Text3d>,
+                Changed<GlobalTransform>,
+                Changed<InheritedVisibility>,
+                Changed<Text3dStyle>,
+                Added<Text3dBatched>,
+            )>,
+        >,
+        Query<(&mut Text3d, Has<Text3dBatched>)>,
+    )>,
+    mut removed_text: RemovedComponents<Text3d>,
+    mut removed_batched: RemovedComponents<Text3dBatched>,
+    mut removed_style: RemovedComponents<Text3dStyle>,
+    mut batches: ResMut<Text3dBatches>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GlyphMaterial>>,
+    mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut instance_meshes: ResMut<GlyphInstanceMeshes>,
+    mut record_writes: ResMut<GlyphRecordWrites>,
+    font_atlas: Res<FontAtlasSets>,
+    config: Res<TextMeshPluginConfig>,
+) {
+    for entity in removed_text.read() {
+        batches.remove_label(entity);
+    }
+    let mut text_query = text_queries.p1();
+    for entity in removed_batched.read() {
+        batches.remove_label(entity);
+        // Back to its own atlas meshes.
+        if let Ok((mut text_mesh, false)) = text_query.get_mut(entity) {
+            text_mesh.mark_meshes_stale();
+        }
+    }
+    for entity in removed_style.read() {
+        // Regroup into the batches of the default style.
+        if let Ok((mut text_mesh, true)) = text_query.get_mut(entity) {
+            text_mesh.invalidate_quads();
+        }
+    }
+
+    for (entity, mut text_mesh, transform, visibility, batched, style, config_override) in
+        text_queries.p0().iter_mut()
+    {
+        let transform_matrix = transform.to_matrix();
+        let mut rebuild =
+            !text_mesh.quads_up_to_date() || batched.is_added() || visibility.is_changed();
+        if !rebuild && let Some(style) = style.as_ref().filter(|style| style.is_changed()) {
+            // Fading and tinting only rewrite the label's record, as long as it stays in its
+            // batches.
+            let style_key = style.key();
+            let mut in_batch = false;
+            for (key, batch) in batches.batches.iter_mut() {
+                let Some(label) = batch.labels.get_mut(&entity) else {
+                    continue;
+                };
+                if key.2 != style_key {
+                    rebuild = true;
+                    break;
+                }
+                label.opacity = style.opacity;
+                label.tint = style.linear_tint();
+                batch.dirty_labels.insert(entity);
+                in_batch = true;
+            }
+            rebuild |= !in_batch;
+        }
+        if !rebuild {
+            if transform.is_changed() {
+                for batch in batches.batches.values_mut() {
+                    if let Some(label) = batch.labels.get_mut(&entity) {
+                        label.transform = transform_matrix;
+                        batch.dirty_labels.insert(entity);
+                    }
+                }
+            }
+            continue;
+        }
+
+        let font_id = text_mesh.font_id();
+        let style = style.as_deref().cloned().unwrap_or_default();
+        let style_key = style.key();
+        let Some(data) = text_mesh.atlas_set(&font_atlas) else {
+            continue;
+        };
+        let config = TextMeshConfigOverride::resolve(config_override, &config);
+        // Hidden labels are left out of every batch until they are shown again.
+        let positioned = if visibility.get() {
+            let padding = style.padding(data.range());
+            text_mesh.position_glyphs(entity, data, config.font_scale.truncate(), padding)
+        } else {
+            Vec::new()
+        };
+
+        let mut atlas_glyphs: HashMap<usize, Vec<&PositionedGlyph>> = HashMap::new();
+        for glyph in positioned.iter() {
+            atlas_glyphs.entry(glyph.index).or_default().push(glyph);
+        }
+
+        // Leave the batches of atlas pages the label no longer uses.
+        for (key, batch) in batches.batches.iter_mut() {
+            if (key.0 != font_id || !atlas_glyphs.contains_key(&key.1) || key.2 != style_key)
+                && batch.labels.remove(&entity).is_some()
+            {
+                batch.glyphs_dirty = true;
+            }
+        }
+
+        let mut complete = true;
+        for (atlas, glyphs) in atlas_glyphs {
+            let key = (font_id, atlas, style_key.clone());
+            let batch = match batches.batches.entry(key) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let Some(sdf_texture) = data.atlas_texture(atlas) else {
+                        complete = false;
+                        continue;
+                    };
+                    let glyph_instances = storage_buffers
+                        .add(ShaderStorageBuffer::from(vec![GlyphInstance::default()]));
+                    let label_records =
+                        storage_buffers.add(GlyphRecordWrites::writable_buffer(vec![
+                            GlyphLabel::default(),
+                        ]));
+                    let material = materials.add(GlyphMaterial {
+                        glyph_instances: glyph_instances.clone(),
+                        labels: label_records.clone(),
+                        instanced: true,
+                        batched: true,
+                        ..style.material(sdf_texture, data.range())
+                    });
+                    let mesh = instance_meshes.mesh(0, &mut meshes);
+                    let batch_entity = commands
+                        .spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(material.clone()),
+                            Transform::IDENTITY,
+                            Visibility::default(),
+                            NotShadowCaster,
+                        ))
+                        .id();
+                    // Screen sized labels are scaled per view, so their bounds aren't known.
+                    if style.screen_size.is_some() {
+                        commands.entity(batch_entity).insert(NoFrustumCulling);
+                    }
+                    info!(
+                        "Created Text3d batch entity={:?} for atlas {} of font {:?}",
+                        batch_entity, atlas, font_id
+                    );
+                    entry.insert(TextBatch {
+                        entity: batch_entity,
+                        material,
+                        glyph_instances,
+                        label_records,
+                        mesh,
+                        labels: HashMap::new(),
+                        aabb: Aabb::default(),
+                        glyphs_dirty: true,
+                        dirty_labels: HashSet::new(),
+                    })
+                }
+            };
+            let mut bounds = Aabb::enclosing(
+                glyphs
+                    .iter()
+                    .flat_map(|pg| quad_corners(&pg.position, pg.transform.as_ref())),
+            )
+            .unwrap_or_default();
+            if style.billboard.is_some() {
+                bounds = billboard_bounds(bounds);
+            }
+            // Labels rebuilt in place keep moving from where they were drawn last frame.
+            let previous_transform = batch
+                .labels
+                .get(&entity)
+                .map_or(transform_matrix, |label| label.previous_transform);
+            batch.labels.insert(
+                entity,
+                BatchLabel {
+                    glyphs: glyphs.iter().map(|pg| pg.instance()).collect(),
+                    bounds,
+                    transform: transform_matrix,
+                    previous_transform,
+                    opacity: style.opacity,
+                    tint: style.linear_tint(),
+                    slot: 0,
+                },
+            );
+            batch.glyphs_dirty = true;
+        }
+
+        if complete {
+            text_mesh.mark_quads_built();
+        } else {
+            // An atlas texture is still being generated; revisit the label next frame.
+            text_mesh.set_changed();
+        }
+    }
+
+    upload_batches(
+        &mut batches,
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut storage_buffers,
+        &mut instance_meshes,
+        &mut record_writes,
+    );
+}
+
+/// Writes the glyph records and label records of every changed batch, and despawns
+/// batches left without labels.
+///
+/// Batches whose labels changed their glyphs are uploaded whole. Labels that only moved or
+/// faded have their records written in place, which keeps the batch's GPU buffers and
+/// material bind group.
+fn upload_batches(
+    batches: &mut Text3dBatches,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<GlyphMaterial>,
+    storage_buffers: &mut Assets<ShaderStorageBuffer>,
+    instance_meshes: &mut GlyphInstanceMeshes,
+    record_writes: &mut GlyphRecordWrites,
+) {
+    batches.batches.retain(|key, batch| {
+        if batch.labels.is_empty() {
+            commands.entity(batch.entity).try_despawn();
+            debug!(
+                "Despawned empty Text3d batch for atlas {} of font {:?}",
+                key.1, key.0
+            );
+            return false;
+        }
+        true
+    });
+
+    for batch in batches.batches.values_mut() {
+        if batch.glyphs_dirty {
+            let mut instances = Vec::new();
+            let mut records = vec![GlyphLabel::default(); batch.labels.len()];
+            batch.dirty_labels.clear();
+            for (slot, (entity, label)) in batch.labels.iter_mut().enumerate() {
+                label.slot = slot as u32;
+                instances.extend(label.glyphs.iter().map(|glyph| GlyphInstance {
+                    label: label.slot,
+                    ..*glyph
+                }));
+                records[slot] = label.record();
+                // Labels that moved are written once more after they stop, so their motion
+                // vectors drop back to zero.
+                if label.previous_transform != label.transform {
+                    label.previous_transform = label.transform;
+                    batch.dirty_labels.insert(*entity);
+                }
+            }
+            let glyph_count = instances.len();
+            upload_glyph_records(
+                instances,
+                &batch.glyph_instances,
+                &batch.material,
+                storage_buffers,
+                materials,
+            );
+            upload_glyph_records(
+                records,
+                &batch.label_records,
+                &batch.material,
+                storage_buffers,
+                materials,
+            );
+
+            let mesh = instance_meshes.mesh(glyph_count, meshes);
+            if mesh != batch.mesh {
+                batch.mesh = mesh.clone();
+                commands.entity(batch.entity).insert(Mesh3d(mesh));
+            }
+
+            batch.aabb = Aabb::enclosing(batch.labels.values().flat_map(BatchLabel::world_corners))
+                .unwrap_or_default();
+            commands.entity(batch.entity).insert(batch.aabb);
+            batch.glyphs_dirty = false;
+            continue;
+        }
+
+        if batch.dirty_labels.is_empty() {
+            continue;
+        }
+        let (mut min, mut max) = (batch.aabb.min(), batch.aabb.max());
+        for entity in std::mem::take(&mut batch.dirty_labels) {
+            let Some(label) = batch.labels.get_mut(&entity) else {
+                continue;
+            };
+            record_writes.write(&batch.label_records, label.slot, &label.record());
+            for corner in label.world_corners() {
+                min = min.min(corner.into());
+                max = max.max(corner.into());
+            }
+            if label.previous_transform != label.transform {
+                label.previous_transform = label.transform;
+                batch.dirty_labels.insert(entity);
+            }
+        }
+        // The bounds only grow until the glyph records are next rebuilt, which spares
+        // visiting every label of the batch whenever one of them moves.
+        if min != batch.aabb.min() || max != batch.aabb.max() {
+            batch.aabb = Aabb::from_min_max(min.into(), max.into());
+            commands.entity(batch.entity).insert(batch.aabb);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::tests::{spawn_text, test_app};
+    use bevy::prelude::{App, Color};
+    use bevy::render::render_resource::encase::StorageBuffer;
+
+    /// Returns the keys of the batches holding `label`.
+    fn batches_of(app: &App, label: Entity) -> Vec<BatchKey> {
+        app.world()
+            .resource::<Text3dBatches>()
+            .batches
+            .iter()
+            .filter(|(_, batch)| batch.labels.contains_key(&label))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_batches_follow_font_and_style() {
+        let (mut app, font) = test_app();
+        let first = spawn_text(&mut app, &font, "AB");
+        let second = spawn_text(&mut app, &font, "AC");
+        for label in [first, second] {
+            app.world_mut()
+                .entity_mut(label)
+                .insert((Text3dBatched, InheritedVisibility::VISIBLE));
+        }
+        app.update();
+        assert_eq!(batches_of(&app, first).len(), 1);
+        assert_eq!(batches_of(&app, first), batches_of(&app, second));
+
+        // Fading and tinting a label keeps it in its batch.
+        app.world_mut().entity_mut(second).insert(Text3dStyle {
+            opacity: 0.5,
+            tint: Color::srgb(1.0, 0.0, 0.0),
+            ..Default::default()
+        });
+        app.update();
+        assert_eq!(batches_of(&app, first), batches_of(&app, second));
+        let batches = app.world().resource::<Text3dBatches>();
+        let label = &batches.batches[&batches_of(&app, second)[0]].labels[&second];
+        assert_eq!(label.opacity, 0.5);
+        assert_eq!(label.tint, Vec4::new(1.0, 0.0, 0.0, 1.0));
+
+        app.world_mut()
+            .get_mut::<Text3dStyle>(second)
+            .unwrap()
+            .smoothing = 2.0;
+        app.update();
+        assert_eq!(batches_of(&app, second).len(), 1);
+        assert_ne!(batches_of(&app, first), batches_of(&app, second));
+
+        // A second copy of the font is a different asset.
+        let font = app.world().resource::<Assets<Font>>().get(&font).cloned();
+        let other_font = app
+            .world_mut()
+            .resource_mut::<Assets<Font>>()
+            .add(font.unwrap());
+        let mut text_mesh = Text3d::new(other_font.clone());
+        text_mesh.set_glyphs(app.world().get::<Text3d>(first).unwrap().glyphs().into());
+        app.world_mut().entity_mut(first).insert(text_mesh);
+        app.update();
+        let keys = batches_of(&app, first);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].0, other_font.id());
+    }
+
+    #[test]
+    fn test_moved_labels_keep_previous_transform() {
+        let (mut app, font) = test_app();
+        let label = spawn_text(&mut app, &font, "AB");
+        app.world_mut()
+            .entity_mut(label)
+            .insert((Text3dBatched, InheritedVisibility::VISIBLE));
+        app.update();
+        let key = batches_of(&app, label)[0].clone();
+        let uploaded = |app: &App| {
+            let batch = &app.world().resource::<Text3dBatches>().batches[&key];
+            let storage = app
+                .world()
+                .resource::<Assets<ShaderStorageBuffer>>()
+                .get(&batch.label_records)
+                .unwrap();
+            let records: Vec<GlyphLabel> = StorageBuffer::new(storage.data.as_deref().unwrap())
+                .create()
+                .unwrap();
+            records[0]
+        };
+        // Returns the record written in place this frame, and whether the label is written
+        // again next frame.
+        let written = |app: &App| {
+            let batch = &app.world().resource::<Text3dBatches>().batches[&key];
+            let writes = app.world().resource::<GlyphRecordWrites>().writes();
+            assert_eq!(writes.len(), 1);
+            let (buffer, offset, bytes) = &writes[0];
+            assert_eq!((*buffer, *offset), (batch.label_records.id(), 0));
+            let record: GlyphLabel = StorageBuffer::new(bytes.as_slice()).create().unwrap();
+            (record, batch.dirty_labels.contains(&label))
+        };
+
+        let moved = Mat4::from_translation(Vec3::X);
+        *app.world_mut().get_mut::<GlobalTransform>(label).unwrap() =
+            GlobalTransform::from_translation(Vec3::X);
+        app.update();
+        let (record, dirty) = written(&app);
+        assert_eq!(record.previous_world_from_label, Mat4::IDENTITY);
+        assert_eq!(record.world_from_label, moved);
+        assert!(dirty);
+
+        // Once the label stops, it is written once more without motion.
+        app.update();
+        let (record, dirty) = written(&app);
+        assert_eq!(record.previous_world_from_label, moved);
+        assert_eq!(record.world_from_label, moved);
+        assert!(!dirty);
+
+        // Moving never re-uploads the table, and the batch bounds grew to cover the label.
+        assert_eq!(uploaded(&app).world_from_label, Mat4::IDENTITY);
+        let batch = &app.world().resource::<Text3dBatches>().batches[&key];
+        assert!(batch.aabb.min().x <= 0.0);
+        assert!(batch.aabb.max().x >= batch.labels[&label].bounds.max().x + 1.0);
+        app.update();
+        assert!(
+            app.world()
+                .resource::<GlyphRecordWrites>()
+                .writes()
+                .is_empty()
+        );
+    }
+}
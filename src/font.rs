@@ -42,6 +42,56 @@ pub struct GlyphInfo {
     pub size: Vec2,
 }
 
+/// Resolution of the SDF atlases a [`Text3d`](crate::Text3d) draws its glyphs from.
+///
+/// Every tier keeps its own atlases per font, with glyphs generated at its size and the SDF
+/// range grown in proportion, so a [`Text3dStyle`](crate::Text3dStyle) reaches equally far
+/// past the glyph edges in all of them. World space text uses `Regular`; screen sized text
+/// uses the tier picked by [`SdfTier::for_pixel_height`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SdfTier {
+    /// 50 texels per em, for text a few dozen pixels high.
+    Small,
+    /// 100 texels per em.
+    #[default]
+    Regular,
+    /// 200 texels per em, for text covering much of the screen.
+    Large,
+}
+
+impl SdfTier {
+    /// Returns the size of an em in atlas texels.
+    pub const fn em_texels(self) -> f64 {
+        match self {
+            Self::Small => 50.0,
+            Self::Regular => 100.0,
+            Self::Large => 200.0,
+        }
+    }
+
+    /// Returns the SDF range in atlas texels.
+    pub const fn range(self) -> u8 {
+        match self {
+            Self::Small => 3,
+            Self::Regular => 6,
+            Self::Large => 12,
+        }
+    }
+
+    /// Returns the tier closest to `pixel_height` screen pixels per em. Minifying an atlas
+    /// much denser than the screen aliases, and magnifying a sparse one rounds the corners.
+    pub fn for_pixel_height(pixel_height: f32) -> Self {
+        // Geometric means of the neighbouring tier sizes.
+        if pixel_height < 70.0 {
+            Self::Small
+        } else if pixel_height < 140.0 {
+            Self::Regular
+        } else {
+            Self::Large
+        }
+    }
+}
+
 #[derive(Asset, TypePath, Clone)]
 pub struct Font {
     pub(crate) face: Arc<owned_ttf_parser::OwnedFace>,
@@ -179,14 +229,15 @@ impl Font {
         builder.shape
     }
 
-    /// Generate a signed distance field (SDF) image for the given glyph.
-    pub fn generate(&self, glyph_id: GlyphId, code_point: char, range: f64) -> Option<Image> {
+    /// Generate a signed distance field (SDF) image for the given glyph, sized for `tier`.
+    pub fn generate(&self, glyph_id: GlyphId, code_point: char, tier: SdfTier) -> Option<Image> {
         let face = self.face.clone();
         let face = face.as_ref().as_face_ref();
+        let range = tier.range() as f64;
 
         debug!(
-            "Generating SDF image for glyph {:?} ('{}', range={:?})",
-            glyph_id, code_point, range
+            "Generating SDF image for glyph {:?} ('{}', tier={:?})",
+            glyph_id, code_point, tier
         );
 
         let units_per_em = face.units_per_em();
@@ -200,9 +251,9 @@ impl Font {
         }
 
         // Normalize glyph coordinates so that the font's
-        // em square is 100x100 units for SDF generation
+        // em square is as many texels wide as the tier asks for
         // Sweet spot for SDF generation scale is best between 0.01 and 0.2
-        let scale = (1.0f64 / units_per_em as f64) * 100f64;
+        let scale = (1.0f64 / units_per_em as f64) * tier.em_texels();
         if !(0.01..=0.2).contains(&scale) {
             warn!(
                 "SDF generation scale ({}) is outside the optimal range (0.01-0.2). This may result in poor quality glyph rendering. Check the font's units_per_em value ({}).",
@@ -495,14 +546,14 @@ pub struct FontAtlasKey(pub u32);
 ///
 /// A `FontAtlasSet` is an [`Asset`].
 ///
-/// There is one `FontAtlasSet` for each font face.
+/// There is one `FontAtlasSet` for each font face and [`SdfTier`].
 #[derive(Debug, TypePath, Asset)]
 pub struct FontAtlasSet {
     font_atlases: HashMap<FontAtlasKey, Vec<FontAtlas>>,
     added: HashSet<char>,
     code_point_to_atlas: HashMap<char, usize>,
     code_point_to_glyph_info: HashMap<char, GlyphInfo>,
-    range: u8,
+    tier: SdfTier,
     line_gap: f64,
 }
 
@@ -513,7 +564,7 @@ impl Default for FontAtlasSet {
             added: Default::default(),
             code_point_to_atlas: Default::default(),
             code_point_to_glyph_info: Default::default(),
-            range: 6,
+            tier: SdfTier::Regular,
             line_gap: 0.0,
         }
     }
@@ -521,16 +572,22 @@ impl Default for FontAtlasSet {
 
 impl FontAtlasSet {
     pub fn from(face: &Font) -> Self {
+        Self::with_tier(face, SdfTier::Regular)
+    }
+
+    /// Creates an empty atlas set generating the glyphs of `face` for `tier`.
+    pub fn with_tier(face: &Font, tier: SdfTier) -> Self {
         debug!(
-            "Creating FontAtlasSet from face with line_gap {}",
-            face.line_gap()
+            "Creating FontAtlasSet from face with line_gap {} for {:?}",
+            face.line_gap(),
+            tier
         );
         Self {
             font_atlases: HashMap::with_capacity_and_hasher(1, Default::default()),
             added: Default::default(),
             code_point_to_atlas: Default::default(),
             code_point_to_glyph_info: Default::default(),
-            range: 6,
+            tier,
             line_gap: face.line_gap(),
         }
     }
@@ -561,7 +618,7 @@ impl FontAtlasSet {
         );
         self.code_point_to_glyph_info
             .insert(code_point, glyph_info.clone());
-        let glyph_texture = match font.generate(glyph_info.id, code_point, self.range as f64) {
+        let glyph_texture = match font.generate(glyph_info.id, code_point, self.tier) {
             Some(tex) => tex,
             None => {
                 warn!(
@@ -630,16 +687,34 @@ impl FontAtlasSet {
         self.code_point_to_atlas.get(&code_point).copied()
     }
 
-    /// Get the atlas index for a given code point.
+    /// Get the normalized atlas rect of a glyph.
     pub fn find_glyph_rect(&self, glyph_id: GlyphId) -> Option<Rect> {
+        self.find_glyph_rect_padded(glyph_id, 0)
+    }
+
+    /// Get the normalized atlas rect of a glyph, grown by `padding` texels into the SDF
+    /// padding around it. The padding is capped at [`FontAtlasSet::range`].
+    pub fn find_glyph_rect_padded(&self, glyph_id: GlyphId, padding: u8) -> Option<Rect> {
         let atlas_key = FontAtlasKey(0);
+        let inset = self.range().saturating_sub(padding);
         self.font_atlases.get(&atlas_key).and_then(|atlases| {
             atlases
                 .iter()
-                .find_map(|atlas| atlas.get_glyph_rect(glyph_id, self.range))
+                .find_map(|atlas| atlas.get_glyph_rect(glyph_id, inset))
         })
     }
 
+    /// Get the SDF range in atlas texels. Glyph images are padded by this many texels on
+    /// every side, and the SDF covers distances up to half of it outside the glyph edge.
+    pub fn range(&self) -> u8 {
+        self.tier.range()
+    }
+
+    /// Get the tier the glyphs are generated for.
+    pub fn tier(&self) -> SdfTier {
+        self.tier
+    }
+
     /// Get the texture handle for a given atlas index.
     pub fn atlas_texture(&self, atlas: usize) -> Option<Handle<Image>> {
         let atlas_key = FontAtlasKey(0);
@@ -655,31 +730,45 @@ impl FontAtlasSet {
     }
 }
 
-/// A map of font faces to their corresponding [`FontAtlasSet`]s.
+/// A map of font faces and [`SdfTier`]s to their corresponding [`FontAtlasSet`]s.
 #[derive(Debug, Default, Resource)]
 pub struct FontAtlasSets {
     // PERF: in theory this could be optimized with Assets storage ... consider making some fast "simple" AssetMap
-    pub(crate) sets: HashMap<AssetId<Font>, FontAtlasSet>,
+    pub(crate) sets: HashMap<(AssetId<Font>, SdfTier), FontAtlasSet>,
 }
 
 impl FontAtlasSets {
-    /// Get a reference to the [`FontAtlasSet`] with the given font asset id.
+    /// Get a reference to the regular tier [`FontAtlasSet`] with the given font asset id.
     pub fn get(&self, id: impl Into<AssetId<Font>>) -> Option<&FontAtlasSet> {
-        let id: AssetId<Font> = id.into();
-        self.sets.get(&id)
+        self.get_tier(id, SdfTier::Regular)
     }
-    /// Get a mutable reference to the [`FontAtlasSet`] with the given font asset id.
+    /// Get a mutable reference to the regular tier [`FontAtlasSet`] with the given font asset id.
     pub fn get_mut(&mut self, id: impl Into<AssetId<Font>>) -> Option<&mut FontAtlasSet> {
-        let id: AssetId<Font> = id.into();
-        self.sets.get_mut(&id)
+        self.sets.get_mut(&(id.into(), SdfTier::Regular))
+    }
+    /// Get a reference to the [`FontAtlasSet`] of `tier` with the given font asset id.
+    pub fn get_tier(&self, id: impl Into<AssetId<Font>>, tier: SdfTier) -> Option<&FontAtlasSet> {
+        self.sets.get(&(id.into(), tier))
+    }
+    /// Add the given code points to the regular tier font atlas set for the specified font
+    /// asset id. See [`FontAtlasSets::add_tier_code_points`].
+    pub fn add_code_points(
+        &mut self,
+        chars: &[char],
+        font_id: AssetId<Font>,
+        fonts: &Assets<Font>,
+        textures: &mut Assets<Image>,
+    ) {
+        self.add_tier_code_points(chars, font_id, SdfTier::Regular, fonts, textures);
     }
-    /// Add the given code points to the font atlas set for the specified font asset id.
+    /// Add the given code points to the font atlas set of `tier` for the specified font asset id.
     /// If the font atlas set does not exist, it will be created.
     /// If a code point is already present, it will be skipped.
-    pub fn add_code_points(
+    pub fn add_tier_code_points(
         &mut self,
         chars: &[char],
         font_id: AssetId<Font>,
+        tier: SdfTier,
         fonts: &Assets<Font>,
         textures: &mut Assets<Image>,
     ) {
@@ -699,9 +788,9 @@ impl FontAtlasSets {
             chars.len(),
             font_id
         );
-        let font_atlas_set = self.sets.entry(font_id).or_insert_with(|| {
+        let font_atlas_set = self.sets.entry((font_id, tier)).or_insert_with(|| {
             debug!("Inserting new FontAtlasSet entry.");
-            FontAtlasSet::from(font)
+            FontAtlasSet::with_tier(font, tier)
         });
         for code_point in chars {
             if !font_atlas_set.has_glyph(*code_point) {
@@ -1,22 +1,36 @@
+mod batching;
 pub mod dump_glyph_profile;
 pub mod font;
 pub mod grounding;
+mod lit;
+mod lod;
 mod pipeline;
 mod pipeline_material;
 pub mod shadow_casting;
+mod style;
 pub mod tessellation;
 mod text;
 
-pub use font::{Font, FontAtlasSet, FontAtlasSets};
+pub use batching::Text3dBatched;
+pub use font::{Font, FontAtlasSet, FontAtlasSets, SdfTier};
+pub use lit::{LitGlyphMaterial, LitGlyphMaterialCache, SdfGlyphExt, Text3dLit};
+pub use lod::Text3dLod;
 // Re-export Bevy's `OnlyShadowCaster` so examples and other crates can import from `bevy_text3d`.
 pub use bevy::light::OnlyShadowCaster;
 pub use pipeline::{
-    Glyph, GlyphProfileRenderMode, GlyphTessellationQuality, Text3d, TextMeshPluginConfig,
+    Glyph, GlyphPlacement, GlyphProfileRenderMode, GlyphTessellationQuality, GlyphTransform,
+    Text3d, TextMeshConfigOverride, TextMeshPluginConfig,
 };
 pub use pipeline_material::DepthOnlyMaterial;
 pub use pipeline_material::GlyphMaterial;
+pub use pipeline_material::GlyphMaterialUniform;
+pub use pipeline_material::MAX_GLYPH_OUTLINES;
 pub use shadow_casting::{
     NoColorExt, ShadowOnlyMaterial, ShadowOnlyMaterialPlugin, ShadowOnlyMeshBundle,
     create_shadow_only_material,
 };
+pub use style::{
+    FillSpace, GlyphAlphaMode, GlyphBevel, GlyphFill, GlyphGlow, GlyphMaterialCache, GlyphOutline,
+    GlyphPulse, GlyphShadow, Text3dBillboard, Text3dDepthMode, Text3dScreenSize, Text3dStyle,
+};
 pub use text::{Text3dConfig, Text3dPlugin};
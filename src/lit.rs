@@ -0,0 +1,356 @@
+//! PBR-lit glyph material: SDF coverage and style from the atlas, shaded by Bevy's
+//! `StandardMaterial` lighting.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::{AssetId, Assets, Handle},
+    color::{ColorToComponents, LinearRgba},
+    pbr::{ExtendedMaterial, MeshMaterial3d, StandardMaterial},
+    prelude::{
+        Added, Asset, Changed, ChildOf, Commands, Component, DetectChanges, Entity, Has, Image, Or,
+        Query, Ref, RemovedComponents, Res, ResMut, Resource, With, Without,
+    },
+    reflect::TypePath,
+    render::{render_resource::AsBindGroup, storage::ShaderStorageBuffer},
+    shader::ShaderRef,
+};
+use bevy_log::{debug, warn};
+
+use crate::{
+    batching::Text3dBatched,
+    font::FontAtlasSets,
+    pipeline::Text3d,
+    pipeline_material::{
+        GLYPH_LABELS, GlyphDepthKey, GlyphMaterial, GlyphMaterialUniform, specialize_glyph_depth,
+        specialize_glyph_vertices,
+    },
+    style::{DEFAULT_STYLE, GlyphMaterialCache, StyleKey, Text3dDepthMode, Text3dStyle},
+};
+
+/// Extension of [`StandardMaterial`] that takes the base color coverage of glyph quads from
+/// an SDF atlas, styled like [`GlyphMaterial`].
+///
+/// Only reads glyph quads from vertex attributes; instanced and batched text stays unlit.
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+#[bind_group_data(GlyphDepthKey)]
+pub struct SdfGlyphExt {
+    #[uniform(100)]
+    pub params: GlyphMaterialUniform,
+    #[texture(101)]
+    #[sampler(102)]
+    pub sdf_texture: Handle<Image>,
+    /// Image sampled by image fills; see [`GlyphMaterialUniform::fill`].
+    #[texture(103)]
+    #[sampler(104)]
+    pub fill_texture: Option<Handle<Image>>,
+    /// Tint and opacity of each label, found at the index in the `MeshTag` of its meshes. Use
+    /// [`GLYPH_LABELS`].
+    #[storage(105, read_only, visibility(vertex))]
+    pub labels: Handle<ShaderStorageBuffer>,
+    /// How the glyphs are depth tested; part of the pipeline specialization key. The depth
+    /// bias of decals comes from the base material.
+    pub depth_mode: Text3dDepthMode,
+}
+
+impl From<&SdfGlyphExt> for GlyphDepthKey {
+    fn from(extension: &SdfGlyphExt) -> Self {
+        extension.depth_mode.into()
+    }
+}
+
+impl bevy::pbr::MaterialExtension for SdfGlyphExt {
+    fn vertex_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_lit.wgsl".into())
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_lit.wgsl".into())
+    }
+
+    fn prepass_vertex_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
+    fn deferred_vertex_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
+    fn specialize(
+        _pipeline: &bevy::pbr::MaterialExtensionPipeline,
+        descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+        layout: &bevy_mesh::MeshVertexBufferLayoutRef,
+        key: bevy::pbr::MaterialExtensionKey<Self>,
+    ) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
+        // The prepass shader is shared with `GlyphMaterial`, whose bindings start at 0.
+        descriptor.vertex.shader_defs.push("GLYPH_LIT".into());
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader_defs.push("GLYPH_LIT".into());
+        }
+        specialize_glyph_depth(descriptor, key.bind_group_data);
+        // Glyph meshes carry their own attributes instead of the standard mesh ones.
+        specialize_glyph_vertices(descriptor, layout)
+    }
+}
+
+/// SDF glyphs shaded by [`StandardMaterial`] lighting.
+pub type LitGlyphMaterial = ExtendedMaterial<StandardMaterial, SdfGlyphExt>;
+
+/// Shades the glyphs of a [`Text3d`] with PBR lighting, so they receive lights, shadows and
+/// `DistanceFog` instead of drawing at their full color.
+///
+/// The [`Text3dStyle`] color, outlines, glow and shadow become the base color of the surface,
+/// drawn with the style's alpha mode.
+///
+/// Only glyphs read from vertex attributes can be lit. Text using
+/// [`Text3d::with_glyph_instancing`] or [`Text3dBatched`] ignores this component, stays unlit
+/// and logs a warning when it's added.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Text3dLit {
+    /// See [`StandardMaterial::perceptual_roughness`].
+    pub perceptual_roughness: f32,
+    /// See [`StandardMaterial::metallic`].
+    pub metallic: f32,
+    /// See [`StandardMaterial::reflectance`].
+    pub reflectance: f32,
+    /// Light emitted by the glyphs regardless of the lights around them, on top of the
+    /// glyph-colored emission of [`Text3dStyle::emissive`].
+    pub emissive: LinearRgba,
+    /// Whether `DistanceFog` is applied to the glyphs.
+    pub fog_enabled: bool,
+}
+
+impl Default for Text3dLit {
+    fn default() -> Self {
+        Self {
+            perceptual_roughness: 0.5,
+            metallic: 0.0,
+            reflectance: 0.5,
+            emissive: LinearRgba::BLACK,
+            fog_enabled: true,
+        }
+    }
+}
+
+impl Text3dLit {
+    /// Returns a lit material drawing glyphs from vertex attributes with `style`, for an
+    /// atlas with the given SDF `range`.
+    pub fn material(
+        &self,
+        style: &Text3dStyle,
+        sdf_texture: Handle<Image>,
+        range: u8,
+    ) -> LitGlyphMaterial {
+        LitGlyphMaterial {
+            base: StandardMaterial {
+                perceptual_roughness: self.perceptual_roughness,
+                metallic: self.metallic,
+                reflectance: self.reflectance,
+                emissive: self.emissive,
+                fog_enabled: self.fog_enabled,
+                alpha_mode: style.pass_alpha_mode(),
+                opaque_render_method: style.opaque_render_method,
+                depth_bias: style.depth_mode.depth_bias(),
+                double_sided: true,
+                cull_mode: None,
+                ..Default::default()
+            },
+            extension: SdfGlyphExt {
+                params: style.uniform(range),
+                sdf_texture,
+                fill_texture: style.fill_image().cloned(),
+                labels: GLYPH_LABELS,
+                depth_mode: style.depth_mode,
+            },
+        }
+    }
+
+    fn key(&self) -> [u32; 8] {
+        let [r, g, b, a] = self.emissive.to_f32_array();
+        [
+            self.perceptual_roughness,
+            self.metallic,
+            self.reflectance,
+            r,
+            g,
+            b,
+            a,
+            if self.fog_enabled { 1.0 } else { 0.0 },
+        ]
+        .map(f32::to_bits)
+    }
+}
+
+/// Lit glyph materials shared by all lit text with the same atlas texture, style and lighting.
+///
+/// Like [`GlyphMaterialCache`], only asset ids are kept and entries of freed materials are
+/// dropped when a new one is created.
+#[derive(Default, Resource)]
+pub struct LitGlyphMaterialCache {
+    materials: HashMap<(AssetId<Image>, StyleKey, [u32; 8]), AssetId<LitGlyphMaterial>>,
+}
+
+impl LitGlyphMaterialCache {
+    /// Returns the shared material for `sdf_texture` drawn with `style` and `lit`, creating
+    /// it if needed. `range` is the SDF range of the font atlas owning the texture.
+    pub(crate) fn material(
+        &mut self,
+        sdf_texture: &Handle<Image>,
+        style: &Text3dStyle,
+        lit: &Text3dLit,
+        range: u8,
+        materials: &mut Assets<LitGlyphMaterial>,
+    ) -> Handle<LitGlyphMaterial> {
+        let key = (sdf_texture.id(), style.key(), lit.key());
+        if let Some(handle) = self
+            .materials
+            .get(&key)
+            .and_then(|id| materials.get_strong_handle(*id))
+        {
+            return handle;
+        }
+        self.materials.retain(|_, id| materials.contains(*id));
+        let handle = materials.add(lit.material(style, sdf_texture.clone(), range));
+        self.materials.insert(key, handle.id());
+        handle
+    }
+}
+
+/// System that swaps the glyph materials of [`Text3dLit`] text for lit ones, and back once
+/// the component is removed.
+///
+/// Only visits text whose lighting or style changed, or that got new atlas children. Runs after
+/// `apply_text3d_style_system`, so restyled or newly created atlas children still holding a
+/// [`GlyphMaterial`] are picked up here.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_text3d_lighting_system(
+    mut commands: Commands,
+    lit_query: Query<(
+        &Text3d,
+        Ref<Text3dLit>,
+        Option<&Text3dStyle>,
+        Has<Text3dBatched>,
+    )>,
+    changed: Query<
+        Entity,
+        (
+            With<Text3dLit>,
+            Or<(Changed<Text3dLit>, Changed<Text3dStyle>)>,
+        ),
+    >,
+    new_children: Query<&ChildOf, Added<MeshMaterial3d<GlyphMaterial>>>,
+    unlit_children: Query<(), With<MeshMaterial3d<GlyphMaterial>>>,
+    text_query: Query<(&Text3d, Option<&Text3dStyle>), Without<Text3dBatched>>,
+    mut removed: RemovedComponents<Text3dLit>,
+    mut cache: ResMut<LitGlyphMaterialCache>,
+    mut materials: ResMut<Assets<LitGlyphMaterial>>,
+    mut glyph_cache: ResMut<GlyphMaterialCache>,
+    mut glyph_materials: ResMut<Assets<GlyphMaterial>>,
+    font_atlas: Res<FontAtlasSets>,
+) {
+    let relit: HashSet<Entity> = changed
+        .iter()
+        .chain(new_children.iter().map(ChildOf::parent))
+        .collect();
+    for entity in relit {
+        let Ok((text_mesh, lit, style, batched)) = lit_query.get(entity) else {
+            continue;
+        };
+        if batched {
+            if lit.is_changed() {
+                warn!("Text3dLit has no effect on batched Text3d ({:?})", entity);
+            }
+            continue;
+        }
+        let Some(data) = text_mesh.atlas_set(&font_atlas) else {
+            continue;
+        };
+        for (atlas, child, instance_material) in text_mesh.atlas_children() {
+            if instance_material.is_some() {
+                if lit.is_changed() {
+                    warn!("Text3dLit has no effect on instanced Text3d ({:?})", entity);
+                }
+                continue;
+            }
+            if !lit.is_changed() && !unlit_children.contains(child) {
+                continue;
+            }
+            let Some(sdf_texture) = data.atlas_texture(atlas) else {
+                continue;
+            };
+            let style = style.unwrap_or(&DEFAULT_STYLE);
+            let material = cache.material(&sdf_texture, style, &lit, data.range(), &mut materials);
+            commands
+                .entity(child)
+                .remove::<MeshMaterial3d<GlyphMaterial>>()
+                .insert(MeshMaterial3d(material));
+            debug!("Lit atlas {} of Text3d ({:?})", atlas, entity);
+        }
+    }
+
+    for entity in removed.read() {
+        let Ok((text_mesh, style)) = text_query.get(entity) else {
+            continue;
+        };
+        let Some(data) = text_mesh.atlas_set(&font_atlas) else {
+            continue;
+        };
+        let style = style.unwrap_or(&DEFAULT_STYLE);
+        for (atlas, child, instance_material) in text_mesh.atlas_children() {
+            if instance_material.is_some() {
+                continue;
+            }
+            let Some(sdf_texture) = data.atlas_texture(atlas) else {
+                continue;
+            };
+            let material =
+                glyph_cache.material(&sdf_texture, style, data.range(), &mut glyph_materials);
+            commands
+                .entity(child)
+                .remove::<MeshMaterial3d<LitGlyphMaterial>>()
+                .insert(MeshMaterial3d(material));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::AlphaMode;
+
+    #[test]
+    fn test_lit_material_cache_shares_equal_lighting() {
+        let mut materials = Assets::<LitGlyphMaterial>::default();
+        let mut cache = LitGlyphMaterialCache::default();
+        let texture = Handle::<Image>::default();
+        let style = Text3dStyle::default();
+
+        let matte = Text3dLit::default();
+        let plain = cache.material(&texture, &style, &matte, 6, &mut materials);
+        assert_eq!(
+            plain,
+            cache.material(&texture, &style, &matte, 6, &mut materials)
+        );
+
+        let glowing = Text3dLit {
+            emissive: LinearRgba::rgb(2.0, 1.0, 0.0),
+            fog_enabled: false,
+            ..matte
+        };
+        let glowing = cache.material(&texture, &style, &glowing, 6, &mut materials);
+        assert_ne!(plain, glowing);
+        let material = materials.get(&glowing).unwrap();
+        assert_eq!(material.base.emissive, LinearRgba::rgb(2.0, 1.0, 0.0));
+        assert!(!material.base.fog_enabled);
+        assert_eq!(material.base.alpha_mode, AlphaMode::Blend);
+        assert_eq!(material.extension.sdf_texture, texture);
+    }
+}
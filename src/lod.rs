@@ -0,0 +1,227 @@
+//! Distance based fading, culling and shadow detail for [`Text3d`].
+
+use std::ops::Range;
+
+use bevy::{
+    camera::{Camera, visibility::VisibilityRange},
+    prelude::{Changed, Commands, Component, GlobalTransform, Query, RemovedComponents, Without},
+};
+use bevy_log::debug;
+
+use crate::pipeline::{GlyphTessellationQuality, Text3d};
+
+/// Fraction of a shadow detail distance the text must come closer than it before switching
+/// back to finer detail, so text hovering around the distance doesn't rebuild its profile
+/// every frame.
+const SHADOW_LOD_HYSTERESIS: f32 = 0.1;
+
+/// Fades a [`Text3d`] out with its distance from the camera, culls it beyond that, and lowers
+/// or drops its glyph profile shadows at range.
+///
+/// The fade is a Bevy [`VisibilityRange`] on the text's meshes, so Bevy culls them per view
+/// beyond `fade.end`, shadows included, and the glyph shaders fade them across it: blended
+/// glyphs lose opacity while masked ones dither away like other meshes. Shadow detail follows
+/// the nearest active camera, since the profile mesh is shared by all views.
+///
+/// Batched text is drawn by entities shared with other labels and ignores this component.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Text3dLod {
+    /// Camera distances over which the text fades out; it is culled beyond the end.
+    pub fade: Range<f32>,
+    /// Quality the glyph profile is tessellated at beyond each camera distance. Only ever
+    /// lowers the configured quality.
+    pub shadow_quality: Vec<(f32, GlyphTessellationQuality)>,
+    /// Camera distance beyond which the text casts no shadows.
+    pub shadow_distance: Option<f32>,
+}
+
+/// Glyph profile detail picked by a [`Text3dLod`] for the current camera distance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ShadowLod {
+    /// The configured quality.
+    #[default]
+    Full,
+    /// At most the given quality.
+    Quality(GlyphTessellationQuality),
+    /// No glyph profile.
+    Disabled,
+}
+
+impl Text3dLod {
+    /// Fades the text out between `fade.start` and `fade.end` from the camera.
+    pub fn new(fade: Range<f32>) -> Self {
+        Self {
+            fade,
+            shadow_quality: Vec::new(),
+            shadow_distance: None,
+        }
+    }
+
+    /// Tessellates the glyph profile at `quality` beyond `distance` from the camera.
+    pub fn with_shadow_quality(mut self, distance: f32, quality: GlyphTessellationQuality) -> Self {
+        self.shadow_quality.push((distance, quality));
+        self
+    }
+
+    /// Drops the glyph profile beyond `distance` from the camera.
+    pub fn with_shadow_distance(mut self, distance: f32) -> Self {
+        self.shadow_distance = Some(distance);
+        self
+    }
+
+    /// Returns the visibility range of the text's meshes.
+    pub(crate) fn visibility_range(&self) -> VisibilityRange {
+        VisibilityRange {
+            start_margin: 0.0..0.0,
+            end_margin: self.fade.start.min(self.fade.end)..self.fade.end,
+            use_aabb: false,
+        }
+    }
+
+    /// Returns the glyph profile detail for text `distance` from the nearest camera, which
+    /// currently uses `current`. Finer detail than `current` is only picked once the text is
+    /// closer than its distance by the [`SHADOW_LOD_HYSTERESIS`] fraction of it.
+    pub(crate) fn shadow_lod(&self, distance: f32, current: ShadowLod) -> ShadowLod {
+        let level = self.level_at(distance);
+        if level != current && self.level_at(distance / (1.0 - SHADOW_LOD_HYSTERESIS)) == current {
+            current
+        } else {
+            level
+        }
+    }
+
+    /// Returns the glyph profile detail at `distance`, without hysteresis.
+    fn level_at(&self, distance: f32) -> ShadowLod {
+        if self.shadow_distance.is_some_and(|max| distance > max) {
+            return ShadowLod::Disabled;
+        }
+        self.shadow_quality
+            .iter()
+            .filter(|(start, _)| distance > *start)
+            // The furthest level reached wins, whatever order they were given in.
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(ShadowLod::Full, |&(_, quality)| ShadowLod::Quality(quality))
+    }
+}
+
+impl ShadowLod {
+    /// Returns `configured`, lowered to the quality of this level.
+    pub(crate) fn quality(self, configured: GlyphTessellationQuality) -> GlyphTessellationQuality {
+        match self {
+            Self::Quality(quality) if quality.tolerance() > configured.tolerance() => quality,
+            _ => configured,
+        }
+    }
+}
+
+/// System that picks the glyph profile detail of [`Text3dLod`] text from its distance to the
+/// nearest active camera, and resets it once the component is removed.
+pub fn update_text3d_lod_system(
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut text_query: Query<(&mut Text3d, &Text3dLod, &GlobalTransform)>,
+    mut plain_query: Query<&mut Text3d, Without<Text3dLod>>,
+    mut removed: RemovedComponents<Text3dLod>,
+) {
+    for entity in removed.read() {
+        if let Ok(mut text_mesh) = plain_query.get_mut(entity)
+            && text_mesh.shadow_lod() != ShadowLod::Full
+        {
+            text_mesh.set_shadow_lod(ShadowLod::Full);
+        }
+    }
+
+    let camera_positions: Vec<_> = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .map(|(_, transform)| transform.translation())
+        .collect();
+    if camera_positions.is_empty() {
+        return;
+    }
+    for (mut text_mesh, lod, transform) in text_query.iter_mut() {
+        let position = transform.translation();
+        let distance = camera_positions
+            .iter()
+            .map(|camera| camera.distance(position))
+            .fold(f32::INFINITY, f32::min);
+        let shadow_lod = lod.shadow_lod(distance, text_mesh.shadow_lod());
+        // Only touch the text when the level changes, so idle text isn't flagged as changed.
+        if text_mesh.shadow_lod() != shadow_lod {
+            debug!(
+                "Text3d at distance {} switches shadow detail to {:?}",
+                distance, shadow_lod
+            );
+            text_mesh.set_shadow_lod(shadow_lod);
+        }
+    }
+}
+
+/// System that keeps the [`VisibilityRange`] of the meshes of [`Text3dLod`] text in sync when
+/// the component changes or is removed. Meshes spawned later get it when they are created.
+pub fn apply_text3d_lod_system(
+    mut commands: Commands,
+    changed: Query<(&Text3d, &Text3dLod), Changed<Text3dLod>>,
+    text_query: Query<&Text3d>,
+    mut removed: RemovedComponents<Text3dLod>,
+) {
+    for (text_mesh, lod) in changed.iter() {
+        let range = lod.visibility_range();
+        for child in text_mesh.mesh_children() {
+            commands.entity(child).insert(range.clone());
+        }
+    }
+    for entity in removed.read() {
+        let Ok(text_mesh) = text_query.get(entity) else {
+            continue;
+        };
+        for child in text_mesh.mesh_children() {
+            commands.entity(child).remove::<VisibilityRange>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_lowers_shadow_quality_with_distance() {
+        let lod = Text3dLod::new(40.0..50.0)
+            .with_shadow_quality(20.0, GlyphTessellationQuality::Minimal)
+            .with_shadow_quality(10.0, GlyphTessellationQuality::Low)
+            .with_shadow_distance(30.0);
+        let full = ShadowLod::Full;
+        assert_eq!(lod.shadow_lod(5.0, full), ShadowLod::Full);
+        assert_eq!(
+            lod.shadow_lod(15.0, full),
+            ShadowLod::Quality(GlyphTessellationQuality::Low)
+        );
+        assert_eq!(
+            lod.shadow_lod(25.0, full),
+            ShadowLod::Quality(GlyphTessellationQuality::Minimal)
+        );
+        assert_eq!(lod.shadow_lod(35.0, full), ShadowLod::Disabled);
+
+        // Coming back closer only restores detail well inside its distance.
+        let disabled = ShadowLod::Disabled;
+        assert_eq!(lod.shadow_lod(28.0, disabled), ShadowLod::Disabled);
+        assert_eq!(
+            lod.shadow_lod(26.0, disabled),
+            ShadowLod::Quality(GlyphTessellationQuality::Minimal)
+        );
+        let low = ShadowLod::Quality(GlyphTessellationQuality::Low);
+        assert_eq!(lod.shadow_lod(9.5, low), low);
+        assert_eq!(lod.shadow_lod(8.5, low), ShadowLod::Full);
+
+        // Levels only ever lower the configured quality.
+        assert_eq!(
+            low.quality(GlyphTessellationQuality::Minimal),
+            GlyphTessellationQuality::Minimal
+        );
+        assert_eq!(
+            low.quality(GlyphTessellationQuality::High),
+            GlyphTessellationQuality::Low
+        );
+        assert_eq!(lod.visibility_range().end_margin, 40.0..50.0);
+    }
+}
@@ -1,32 +1,51 @@
-use std::{
-    collections::{HashMap, HashSet, hash_map::DefaultHasher},
-    hash::{Hash, Hasher},
-};
+use std::collections::{HashMap, HashSet};
 
 use bevy::{
     asset::{AssetId, Assets, Handle, RenderAssetUsages},
+    camera::{
+        primitives::Aabb,
+        visibility::{NoFrustumCulling, VisibilitySystems},
+    },
+    ecs::{lifecycle::HookContext, world::DeferredWorld},
     light::{NotShadowCaster, OnlyShadowCaster},
-    math::{Rect, Vec3},
+    math::{Mat2, Rect, Vec2, Vec3, Vec4},
     pbr::StandardMaterial,
     prelude::{
-        AlphaMode, App, Color, Commands, Component, Entity, Image, InheritedVisibility,
-        IntoScheduleConfigs, Mesh, Mesh3d, MeshMaterial3d, Plugin, PostUpdate, Query, Res, ResMut,
-        Resource, Transform, Update, ViewVisibility, Visibility,
+        AlphaMode, App, Changed, Color, Commands, Component, DetectChanges, DetectChangesMut,
+        Entity, First, Has, Image, InheritedVisibility, IntoScheduleConfigs, Mesh, Mesh3d,
+        MeshMaterial3d, Or, Plugin, PostUpdate, Query, RemovedComponents, Res, ResMut, Resource,
+        Transform, Update, ViewVisibility, Visibility, With, Without,
     },
+    render::{ExtractSchedule, Render, RenderApp, RenderSystems, storage::ShaderStorageBuffer},
+    transform::TransformSystems,
 };
 use bevy_log::{debug, info, warn};
 use bevy_mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
 
 use crate::{
-    font::{Font, FontAtlasSets},
-    pipeline_material::{ATTRIBUTE_POSITION, GlyphMaterial},
+    batching::{Text3dBatched, Text3dBatches, update_text_batches_system},
+    font::{Font, FontAtlasSet, FontAtlasSets, GlyphInfo, SdfTier},
+    lit::{LitGlyphMaterialCache, apply_text3d_lighting_system},
+    lod::{ShadowLod, Text3dLod, apply_text3d_lod_system, update_text3d_lod_system},
+    pipeline_material::{
+        ATTRIBUTE_COLOR, ATTRIBUTE_GLYPH_COORD, ATTRIBUTE_GLYPH_EMISSIVE,
+        ATTRIBUTE_GLYPH_VERTEX_INDEX, ATTRIBUTE_POSITION, ATTRIBUTE_UV, EMPTY_GLYPH_INSTANCES,
+        GLYPH_LABELS, GlyphInstance, GlyphMaterial, GlyphRecordWrites,
+        clear_glyph_record_writes_system, extract_glyph_record_writes_system, upload_glyph_records,
+        write_glyph_records_system,
+    },
+    style::{
+        DEFAULT_STYLE, GlyphLabels, GlyphMaterialCache, StyleKey, Text3dStyle,
+        apply_text3d_style_system, select_sdf_tier_system, upload_glyph_labels_system,
+        warn_xray_without_depth_prepass_system,
+    },
 };
 
 // The remainder of the file is the original 'pipeline.rs' content from open_space_mmo
 // which defines the Text3d component, Text3dBuilder, mesh systems, and plugin.
 
-// TODO: Add support for per-character animation, scale, rotation, color, shadow, extrusion depth, etc.
-// This would likely involve increasing the entity count considerably.
+// TODO: Add support for per-character shadow, extrusion depth, etc.
+// Per-character rotation, scale, shear and depth are handled by `GlyphTransform`.
 
 /// Represents the quality level for glyph tessellation.
 /// Lower quality values produce fewer triangles but lower visual fidelity.
@@ -77,6 +96,139 @@ pub struct PositionedGlyph {
     pub uv: Rect,
     pub index: usize,
     pub color: [f32; 4],
+    pub emissive: f32,
+    pub transform: Option<GlyphTransform>,
+}
+
+impl PositionedGlyph {
+    /// Packs the glyph into the record read by the instanced vertex shader.
+    pub(crate) fn instance(&self) -> GlyphInstance {
+        let transform = self.transform.unwrap_or_default();
+        GlyphInstance {
+            rect: Vec4::new(
+                self.position.min.x,
+                self.position.min.y,
+                self.position.max.x,
+                self.position.max.y,
+            ),
+            uv: Vec4::new(self.uv.min.x, self.uv.min.y, self.uv.max.x, self.uv.max.y),
+            color: Vec4::from_array(self.color),
+            linear: Vec4::from_array(transform.linear().to_cols_array()),
+            z_offset: transform.z_offset,
+            atlas: self.index as u32,
+            label: 0,
+            emissive: self.emissive,
+        }
+    }
+}
+
+/// Returns the four corners of a glyph quad in text space, transformed around its center.
+pub(crate) fn quad_corners(rect: &Rect, transform: Option<&GlyphTransform>) -> [Vec3; 4] {
+    [
+        Vec2::new(rect.min.x, rect.min.y),
+        Vec2::new(rect.max.x, rect.min.y),
+        Vec2::new(rect.max.x, rect.max.y),
+        Vec2::new(rect.min.x, rect.max.y),
+    ]
+    .map(|corner| match transform {
+        Some(transform) => transform.apply(rect.center(), corner),
+        None => corner.extend(0.0),
+    })
+}
+
+/// Grows text space bounds to contain the text turned about its origin in any direction, as
+/// billboards are.
+pub(crate) fn billboard_bounds(aabb: Aabb) -> Aabb {
+    let reach = (aabb.center.abs() + aabb.half_extents).length();
+    Aabb::from_min_max(Vec3::splat(-reach), Vec3::splat(reach))
+}
+
+/// How the culling bounds of a text's atlas quads fit the ways its style places it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum QuadBounds {
+    /// Around the glyphs, for text kept where its transform puts it.
+    #[default]
+    Glyphs,
+    /// Around the glyphs turned about the text origin in any direction, see
+    /// [`billboard_bounds`].
+    Billboard,
+    /// None; screen sized text is scaled per view, so it isn't frustum culled.
+    Unbounded,
+}
+
+impl QuadBounds {
+    /// Returns the bounds fitting text with the given style.
+    pub(crate) fn of(style: Option<&Text3dStyle>) -> Self {
+        match style {
+            Some(style) if style.screen_size.is_some() => Self::Unbounded,
+            Some(style) if style.billboard.is_some() => Self::Billboard,
+            _ => Self::Glyphs,
+        }
+    }
+}
+
+/// Grows a glyph quad to cover `padded_uv` instead of `uv`, keeping its texel size.
+pub(crate) fn pad_quad(quad: &Rect, uv: &Rect, padded_uv: &Rect) -> Rect {
+    let uv_size = uv.size();
+    let scale = Vec2::select(uv_size.cmpgt(Vec2::ZERO), quad.size() / uv_size, Vec2::ZERO);
+    let grow = (padded_uv.size() - uv_size) * 0.5 * scale;
+    Rect::from_corners(quad.min - grow, quad.max + grow)
+}
+
+/// An affine transform applied to a single glyph around the center of its quad.
+///
+/// The glyph is scaled, then sheared, then rotated within the text plane, and finally
+/// moved along the text's local Z axis. Both the SDF quad and the glyph profile mesh
+/// apply the same transform, so shadows follow the glyph.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphTransform {
+    /// Counter-clockwise rotation in radians within the text plane.
+    pub rotation: f32,
+    /// Non-uniform scale along the glyph's local X and Y axes.
+    pub scale: Vec2,
+    /// Shear factors: `x` offsets X per unit of Y (a slant), `y` offsets Y per unit of X.
+    pub shear: Vec2,
+    /// Offset along the text's local Z axis.
+    pub z_offset: f32,
+}
+
+impl GlyphTransform {
+    /// The transform that leaves a glyph unchanged.
+    pub const IDENTITY: Self = Self {
+        rotation: 0.0,
+        scale: Vec2::ONE,
+        shear: Vec2::ZERO,
+        z_offset: 0.0,
+    };
+
+    /// Transforms `point` relative to `pivot`, returning a position in text space.
+    pub fn apply(&self, pivot: Vec2, point: Vec2) -> Vec3 {
+        (pivot + self.linear() * (point - pivot)).extend(self.z_offset)
+    }
+
+    /// The rotation, shear and scale part of the transform.
+    fn linear(&self) -> Mat2 {
+        let shear = Mat2::from_cols(Vec2::new(1.0, self.shear.y), Vec2::new(self.shear.x, 1.0));
+        Mat2::from_angle(self.rotation) * shear * Mat2::from_diagonal(self.scale)
+    }
+}
+
+impl Default for GlyphTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// How a [`Glyph`]'s `position` is interpreted when building its quad and profile.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GlyphPlacement {
+    /// `position.min` is the cursor origin; the font's glyph offset and size
+    /// (scaled by [`TextMeshPluginConfig::font_scale`]) determine the final quad.
+    #[default]
+    Cursor,
+    /// `position` is the final quad. The glyph is stretched to fill it exactly,
+    /// without applying the font offset or `font_scale`.
+    Rect,
 }
 
 /// A single glyph to be rendered, including its character, position, and color.
@@ -84,6 +236,13 @@ pub struct Glyph {
     pub position: Rect,
     pub character: char,
     pub color: [f32; 4],
+    /// Light emitted by the glyph as a multiple of its color, added to the
+    /// [`Text3dStyle::emissive`] of its text. Above zero the glyph renders brighter than its
+    /// color, past 1.0 on HDR cameras, where `Bloom` picks it up.
+    pub emissive: f32,
+    pub placement: GlyphPlacement,
+    /// Optional per-glyph transform applied around the center of the glyph's quad.
+    pub transform: Option<GlyphTransform>,
 }
 
 impl Clone for Glyph {
@@ -92,6 +251,9 @@ impl Clone for Glyph {
             position: self.position,
             character: self.character,
             color: self.color,
+            emissive: self.emissive,
+            placement: self.placement,
+            transform: self.transform,
         }
     }
 }
@@ -112,6 +274,9 @@ impl Glyph {
             position,
             character,
             color,
+            emissive: 0.0,
+            placement: GlyphPlacement::Cursor,
+            transform: None,
         }
     }
 
@@ -128,8 +293,63 @@ impl Glyph {
             position,
             character,
             color,
+            emissive: 0.0,
+            placement: GlyphPlacement::Rect,
+            transform: None,
+        }
+    }
+
+    /// Applies a per-glyph transform (rotation, scale, shear and Z offset) to this glyph.
+    pub fn with_transform(mut self, transform: GlyphTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Makes the glyph emit light; see [`Glyph::emissive`].
+    pub fn with_emissive(mut self, emissive: f32) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    /// Transforms a point in quad space by this glyph's transform, pivoting around
+    /// the center of `quad`.
+    fn transform_point(&self, quad: &Rect, point: Vec2) -> Vec3 {
+        match &self.transform {
+            Some(transform) => transform.apply(quad.center(), point),
+            None => point.extend(0.0),
+        }
+    }
+
+    /// Returns the final quad covered by this glyph, given the font metrics of its
+    /// character and the global font scale.
+    pub fn quad_rect(&self, info: &GlyphInfo, font_scale: Vec2) -> Rect {
+        match self.placement {
+            GlyphPlacement::Cursor => {
+                let min = self.position.min + info.offset;
+                Rect::from_corners(min, min + info.size * font_scale)
+            }
+            GlyphPlacement::Rect => self.position,
         }
     }
+
+    /// Maps a point of the glyph's tessellated profile (normalized so the glyph's
+    /// bounding box spans `0..info.size`) into the same space as [`Glyph::quad_rect`].
+    fn profile_point(quad: &Rect, info: &GlyphInfo, point: Vec2) -> Vec2 {
+        let size = quad.size();
+        let scale = Vec2::new(
+            if info.size.x > 0.0 {
+                size.x / info.size.x
+            } else {
+                0.0
+            },
+            if info.size.y > 0.0 {
+                size.y / info.size.y
+            } else {
+                0.0
+            },
+        );
+        quad.min + point * scale
+    }
 }
 
 /// Controls how glyph profile meshes are rendered for shadow casting and physics interactions.
@@ -141,6 +361,11 @@ pub enum GlyphProfileRenderMode {
     DepthOnly,
     /// Render glyph profile mesh with visible material for debugging shadow casting.
     Visible,
+    /// Cast shadows from the SDF atlas quads themselves, discarding fragments outside the
+    /// glyph coverage. No profile mesh is tessellated.
+    ///
+    /// Batched text does not cast shadows in this mode.
+    SdfMasked,
 }
 
 impl Default for GlyphProfileRenderMode {
@@ -151,7 +376,13 @@ impl Default for GlyphProfileRenderMode {
 
 /// A 3D text mesh component that manages glyph rendering through atlas-based meshes.
 /// This component handles the creation and updating of text geometry, materials, and child entities.
+///
+/// When the component is removed or replaced, its atlas and glyph profile child entities are
+/// despawned. The per-atlas `Mesh` and `GlyphMaterial` assets are released once the last handle
+/// to them is dropped.
 #[derive(Component)]
+#[require(Transform, Visibility)]
+#[component(on_replace = despawn_text3d_children)]
 pub struct Text3d {
     font: Handle<Font>,
     missing: Vec<char>,
@@ -160,6 +391,8 @@ pub struct Text3d {
     child_entities: HashMap<usize, Entity>,
     glyph_profile_mesh: Option<Handle<Mesh>>,
     glyph_profile_child_entity: Option<Entity>,
+    // Render mode the glyph profile child entity was spawned with
+    glyph_profile_child_mode: GlyphProfileRenderMode,
     // Track last observed mesh attribute counts per-atlas to avoid
     // logging identical information every frame.
     last_mesh_counts: HashMap<usize, (usize, usize, usize, usize)>,
@@ -167,10 +400,36 @@ pub struct Text3d {
     // atlas so we don't flood the logs repeatedly each frame.
     #[cfg(debug_assertions)]
     logged_missing_glyphs: HashSet<char>,
-    // Cache hash of glyph data to avoid rebuilding meshes when unchanged
-    glyphs_hash: Option<u64>,
+    // Bumped whenever the glyphs, the atlases backing them or the mesh config change.
+    generation: u64,
+    // Generation the per-atlas child meshes and entities were last synced for
+    atlas_generation: Option<u64>,
+    // Tier of the atlases the glyphs are drawn from, and the tier the children were created for
+    sdf_tier: SdfTier,
+    atlas_tier: SdfTier,
+    // Generation the atlas quads were last built for
+    mesh_generation: Option<u64>,
+    // Texels the atlas quads were grown by to fit the style's outlines
+    quad_padding: u8,
+    // How the culling bounds of the atlas quads were last fit to the style
+    quad_bounds: QuadBounds,
+    // Key of the style the atlas materials were last applied for
+    material_key: Option<StyleKey>,
+    // Generation the glyph profile mesh was last built for
+    glyph_profile_generation: Option<u64>,
+    // Whether the glyph profile was last skipped for screen sized text
+    glyph_profile_screen_sized: bool,
+    // Whether the glyph profile was last skipped for billboarded text
+    glyph_profile_billboard: bool,
+    // Profile detail picked by `Text3dLod`, and the detail the profile was last built for
+    shadow_lod: ShadowLod,
+    glyph_profile_shadow_lod: ShadowLod,
     // Controls how glyph profile meshes are rendered for shadow casting
     glyph_profile_render_mode: GlyphProfileRenderMode,
+    // Render glyphs from a per-atlas instance buffer instead of per-vertex quads
+    glyph_instancing: bool,
+    // Instance buffer and the material binding it, per atlas; only used with `glyph_instancing`
+    glyph_instances: HashMap<usize, (Handle<ShaderStorageBuffer>, Handle<GlyphMaterial>)>,
 }
 
 // TODO: Our font atlas implementation vs. that of bevy's Text2d is justified due
@@ -191,11 +450,26 @@ impl Text3d {
             child_entities: Default::default(),
             glyph_profile_mesh: None,
             glyph_profile_child_entity: None,
+            glyph_profile_child_mode: Default::default(),
             last_mesh_counts: Default::default(),
             #[cfg(debug_assertions)]
             logged_missing_glyphs: Default::default(),
-            glyphs_hash: None,
+            generation: 0,
+            atlas_generation: None,
+            sdf_tier: SdfTier::Regular,
+            atlas_tier: SdfTier::Regular,
+            mesh_generation: None,
+            quad_padding: 0,
+            quad_bounds: QuadBounds::Glyphs,
+            material_key: None,
+            glyph_profile_generation: None,
+            glyph_profile_screen_sized: false,
+            glyph_profile_billboard: false,
+            shadow_lod: ShadowLod::Full,
+            glyph_profile_shadow_lod: ShadowLod::Full,
             glyph_profile_render_mode: Default::default(),
+            glyph_instancing: false,
+            glyph_instances: Default::default(),
         }
     }
 
@@ -210,11 +484,26 @@ impl Text3d {
             child_entities: Default::default(),
             glyph_profile_mesh: None,
             glyph_profile_child_entity: None,
+            glyph_profile_child_mode: Default::default(),
             last_mesh_counts: Default::default(),
             #[cfg(debug_assertions)]
             logged_missing_glyphs: Default::default(),
-            glyphs_hash: None,
+            generation: 0,
+            atlas_generation: None,
+            sdf_tier: SdfTier::Regular,
+            atlas_tier: SdfTier::Regular,
+            mesh_generation: None,
+            quad_padding: 0,
+            quad_bounds: QuadBounds::Glyphs,
+            material_key: None,
+            glyph_profile_generation: None,
+            glyph_profile_screen_sized: false,
+            glyph_profile_billboard: false,
+            shadow_lod: ShadowLod::Full,
+            glyph_profile_shadow_lod: ShadowLod::Full,
             glyph_profile_render_mode: self.glyph_profile_render_mode,
+            glyph_instancing: self.glyph_instancing,
+            glyph_instances: Default::default(),
         }
     }
 
@@ -223,6 +512,26 @@ impl Text3d {
         self.font.id()
     }
 
+    /// Returns the tier of the atlases the glyphs are drawn from, picked from the
+    /// [`Text3dStyle`].
+    pub fn sdf_tier(&self) -> SdfTier {
+        self.sdf_tier
+    }
+
+    /// Draws the glyphs from the atlases of `tier`, queueing them for generation there and
+    /// replacing the atlas meshes next frame.
+    pub(crate) fn set_sdf_tier(&mut self, tier: SdfTier) {
+        self.sdf_tier = tier;
+        self.atlas_generation = None;
+        self.mesh_generation = None;
+        self.queue_glyph_code_points();
+    }
+
+    /// Returns the atlases the glyphs are drawn from, once any were generated.
+    pub(crate) fn atlas_set<'a>(&self, atlases: &'a FontAtlasSets) -> Option<&'a FontAtlasSet> {
+        atlases.get_tier(self.font_id(), self.sdf_tier)
+    }
+
     /// Returns the font name if available, for debugging purposes.
     /// This extracts the font family name from the TTF name table.
     pub fn font_name(&self, fonts: &Assets<Font>) -> Option<String> {
@@ -230,14 +539,37 @@ impl Text3d {
     }
 
     /// Adds code points to the list of missing glyphs that need atlas generation.
+    ///
+    /// This is only a hint: [`Text3d::set_glyphs`] already queues every code point
+    /// used by the new glyphs. Use it to warm the atlas with characters that will
+    /// be shown later (e.g. the digits of a counter).
     pub fn add_missing(&mut self, missing: &[char]) {
         self.missing.extend_from_slice(missing);
+        self.dedup_missing();
     }
 
-    /// Returns a slice of the current glyphs to be rendered.
+    /// Replaces the glyphs to be rendered.
+    ///
+    /// Every code point used by `glyphs` is queued for atlas generation; code points
+    /// already present in the font's [`FontAtlasSet`](crate::FontAtlasSet) are skipped
+    /// by [`update_font_atlases_system`], so calling this every frame is cheap.
     pub fn set_glyphs(&mut self, glyphs: Box<[Glyph]>) {
         self.glyphs = glyphs;
-        self.glyphs_hash = None; // Invalidate cached hash
+        self.generation += 1;
+        self.queue_glyph_code_points();
+    }
+
+    /// Queues the code points of the current glyphs that are not yet in `missing`.
+    fn queue_glyph_code_points(&mut self) {
+        self.missing
+            .extend(self.glyphs.iter().map(|glyph| glyph.character));
+        self.dedup_missing();
+    }
+
+    /// Drops repeated code points from `missing`.
+    fn dedup_missing(&mut self) {
+        self.missing.sort_unstable();
+        self.missing.dedup();
     }
 
     /// Return a clone of the glyph profile mesh handle if one has been created.
@@ -259,23 +591,219 @@ impl Text3d {
         self
     }
 
-    /// Clears the glyph profile mesh and child entity, forcing recreation on the next frame.
+    /// Changes the glyph profile render mode of an already spawned Text3d.
+    /// The profile child entity is replaced on the next frame; the profile mesh is kept.
+    pub fn set_glyph_profile_mode(&mut self, mode: GlyphProfileRenderMode) {
+        if self.glyph_profile_render_mode != mode {
+            self.glyph_profile_render_mode = mode;
+            self.glyph_profile_generation = None;
+        }
+    }
+
+    /// Renders the glyphs from a storage buffer holding one record per glyph instead of
+    /// building four vertices and six indices per glyph. Updating the glyphs then only
+    /// rewrites the records; the quads are expanded in the vertex shader from a mesh
+    /// shared by all instanced text of similar length.
+    ///
+    /// Requires storage buffers in vertex shaders, which WebGL2 does not support.
+    pub fn with_glyph_instancing(mut self, enabled: bool) -> Self {
+        self.glyph_instancing = enabled;
+        self
+    }
+
+    /// Returns the glyph profile render mode of this Text3d.
+    pub fn glyph_profile_mode(&self) -> GlyphProfileRenderMode {
+        self.glyph_profile_render_mode
+    }
+
+    /// Marks the glyph profile mesh as stale, forcing it to be rebuilt on the next frame.
     /// Used to change text glyph shadow caster tessellation quality settings.
+    ///
+    /// The existing mesh asset and child entity are updated in place.
     pub fn clear_glyph_profile(&mut self) {
-        // TODO: Investigate need to implement a system to cleanup(or update?) existing abandoned child entities
-        // TODO: Investigate doing this more gracefully without orphaning entities
         // TODO: Consider async glyph recreation - what if there are many text entities?
         debug!(
             "Clearing glyph profile for Text3d - mesh: {:?}, child: {:?}",
             self.glyph_profile_mesh, self.glyph_profile_child_entity
         );
-        self.glyph_profile_mesh = None;
-        self.glyph_profile_child_entity = None;
+        self.glyph_profile_generation = None;
+    }
+
+    /// Forces both the atlas quads and the glyph profile mesh to be rebuilt next frame.
+    pub(crate) fn mark_meshes_stale(&mut self) {
+        self.generation += 1;
+    }
+
+    /// Returns the atlas index and child entity of every atlas mesh, along with the material
+    /// owned by the entity if its glyphs are instanced.
+    pub(crate) fn atlas_children(
+        &self,
+    ) -> impl Iterator<Item = (usize, Entity, Option<&Handle<GlyphMaterial>>)> + '_ {
+        self.child_entities.iter().map(|(&atlas, &child)| {
+            let material = self
+                .glyph_instances
+                .get(&atlas)
+                .map(|(_, material)| material);
+            (atlas, child, material)
+        })
+    }
+
+    /// Forces the atlas quads, but not the glyph profile mesh, to be rebuilt next frame.
+    pub(crate) fn invalidate_quads(&mut self) {
+        self.mesh_generation = None;
+    }
+
+    /// Returns the texels the quads were last grown by around each glyph.
+    pub(crate) fn quad_padding(&self) -> u8 {
+        self.quad_padding
+    }
+
+    /// Returns the key of the style the atlas materials were last applied for.
+    pub(crate) fn material_key(&self) -> Option<&StyleKey> {
+        self.material_key.as_ref()
+    }
+
+    /// Records the key of the style the atlas materials were applied for.
+    pub(crate) fn set_material_key(&mut self, key: StyleKey) {
+        self.material_key = Some(key);
+    }
+
+    /// Returns the glyph profile detail picked for the camera distance.
+    pub(crate) fn shadow_lod(&self) -> ShadowLod {
+        self.shadow_lod
+    }
+
+    /// Sets the glyph profile detail, rebuilding the profile next frame if it differs.
+    pub(crate) fn set_shadow_lod(&mut self, shadow_lod: ShadowLod) {
+        self.shadow_lod = shadow_lod;
+    }
+
+    /// Returns the child entities drawing the atlas quads and the glyph profile.
+    pub(crate) fn mesh_children(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.child_entities
+            .values()
+            .copied()
+            .chain(self.glyph_profile_child_entity)
+    }
+
+    /// Returns how the culling bounds of the quads were last fit to the style.
+    pub(crate) fn quad_bounds(&self) -> QuadBounds {
+        self.quad_bounds
+    }
+
+    /// Returns whether the quads were built for the current glyphs.
+    pub(crate) fn quads_up_to_date(&self) -> bool {
+        self.mesh_generation == Some(self.generation)
+    }
+
+    /// Records that the quads were built for the current glyphs.
+    pub(crate) fn mark_quads_built(&mut self) {
+        self.mesh_generation = Some(self.generation);
+    }
+
+    /// Lays out the glyphs that have atlas UVs as quads in text space, each grown by
+    /// `padding` atlas texels to make room for outlines.
+    ///
+    /// Glyphs without an atlas entry yet are skipped and logged once.
+    pub(crate) fn position_glyphs(
+        &mut self,
+        entity: Entity,
+        data: &FontAtlasSet,
+        font_scale: Vec2,
+        padding: u8,
+    ) -> Vec<PositionedGlyph> {
+        self.quad_padding = padding;
+        let mut positioned: Vec<PositionedGlyph> = Vec::new();
+        // Because we need to mutate `self.logged_missing_glyphs`, collect
+        // newly-missing glyphs first to avoid mutable/immutable borrow conflicts.
+        let mut newly_missing: Vec<char> = Vec::new();
+        for glyph in self.glyphs.iter() {
+            let info_opt = data.glyph_info(glyph.character);
+            if info_opt.is_none() {
+                info!(
+                    "Text3d ({:?}) for ({}) not ready; will be available in a future frame once atlas generation completes",
+                    entity, glyph.character
+                );
+                continue;
+            }
+            let info = info_opt.unwrap();
+
+            match data.atlas(glyph.character) {
+                Some(atlas_idx) => {
+                    if let Some(uv_rect) = data.find_glyph_rect(info.id) {
+                        let mut pos_rect = glyph.quad_rect(info, font_scale);
+                        let mut uv_rect = uv_rect;
+                        if padding > 0
+                            && let Some(padded_uv) = data.find_glyph_rect_padded(info.id, padding)
+                        {
+                            pos_rect = pad_quad(&pos_rect, &uv_rect, &padded_uv);
+                            uv_rect = padded_uv;
+                        }
+                        positioned.push(PositionedGlyph {
+                            position: pos_rect,
+                            uv: uv_rect,
+                            index: atlas_idx,
+                            color: glyph.color,
+                            emissive: glyph.emissive,
+                            transform: glyph.transform,
+                        });
+                    } else {
+                        warn!(
+                            "Text3d ({:?}) glyph={} has atlas entry but no uv rect; skipping quad",
+                            entity, glyph.character
+                        );
+                    }
+                }
+                None => {
+                    #[cfg(debug_assertions)]
+                    if !self.logged_missing_glyphs.contains(&glyph.character) {
+                        newly_missing.push(glyph.character);
+                    }
+                    #[cfg(not(debug_assertions))]
+                    {
+                        newly_missing.push(glyph.character);
+                    }
+                }
+            }
+        }
+
+        for c in newly_missing.into_iter() {
+            warn!("Text3d ({:?}) glyph={} has no atlas!", entity, c);
+            #[cfg(debug_assertions)]
+            self.logged_missing_glyphs.insert(c);
+        }
+
+        positioned
+    }
+}
+
+/// Despawns the child entities owned by a [`Text3d`] that is being removed or replaced.
+fn despawn_text3d_children(mut world: DeferredWorld, HookContext { entity, .. }: HookContext) {
+    let Some(text_mesh) = world.get::<Text3d>(entity) else {
+        return;
+    };
+    let children: Vec<Entity> = text_mesh.mesh_children().collect();
+    debug!(
+        "Despawning {} child entities of Text3d entity ({:?})",
+        children.len(),
+        entity
+    );
+    let mut commands = world.commands();
+    for child in children {
+        // The children may already be gone if the parent is being despawned recursively.
+        commands.entity(child).try_despawn();
+    }
+    if let Some(mut labels) = world.get_resource_mut::<GlyphLabels>() {
+        labels.release(entity);
     }
 }
 
 /// System that processes missing code points for Text3d entities and adds them to font atlases.
 /// This ensures that all required glyphs are available in texture atlases before mesh creation.
+///
+/// Code points are queued by [`Text3d::set_glyphs`] and [`Text3d::add_missing`]. They stay
+/// queued until the font asset has loaded, so text spawned before its font is ready still
+/// gets its atlas entries.
 pub fn update_font_atlases_system(
     mut query: Query<&mut Text3d>,
     mut atlases: ResMut<FontAtlasSets>,
@@ -283,22 +811,65 @@ pub fn update_font_atlases_system(
     fonts: Res<Assets<Font>>,
 ) {
     for mut text_mesh in query.iter_mut() {
-        if !text_mesh.missing.is_empty() {
-            atlases.add_code_points(
-                &text_mesh.missing,
-                text_mesh.font_id(),
-                &fonts,
-                &mut textures,
-            );
-            text_mesh.missing.clear();
+        if text_mesh.missing.is_empty() {
+            continue;
+        }
+        // Keep the queue until the font has loaded; `add_code_points` would drop it otherwise.
+        if fonts.get(text_mesh.font_id()).is_none() {
+            continue;
+        }
+        let font_id = text_mesh.font_id();
+        let tier = text_mesh.sdf_tier();
+        let has_glyph = |atlases: &FontAtlasSets, code_point: char| {
+            atlases
+                .get_tier(font_id, tier)
+                .is_some_and(|set| set.has_glyph(code_point))
+        };
+        let absent: Vec<char> = text_mesh
+            .missing
+            .drain(..)
+            .filter(|&code_point| !has_glyph(&atlases, code_point))
+            .collect();
+        if absent.is_empty() {
+            continue;
+        }
+        atlases.add_tier_code_points(&absent, font_id, tier, &fonts, &mut textures);
+        // Glyphs that were waiting on the atlas can now be meshed.
+        if absent
+            .iter()
+            .any(|&code_point| has_glyph(&atlases, code_point))
+        {
+            text_mesh.mark_meshes_stale();
         }
     }
 }
 
 /// Create meshes for each text character in a `Text3d` that doesn't have
 /// a mesh yet.
+///
+/// Keeps one atlas mesh and child entity per atlas page referenced by the glyphs:
+/// pages gained after `set_glyphs` get a new mesh and material, pages no longer
+/// referenced have their child entity despawned.
+///
+/// Only visits text whose `Text3d`, style or batching changed; text waiting on its font or
+/// atlas textures is flagged as changed to be revisited next frame.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn create_shadow_caster_meshes_system(
-    mut query: Query<(Entity, &mut Text3d)>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Text3d,
+            Option<&TextMeshConfigOverride>,
+            Has<Text3dBatched>,
+            Option<&Text3dStyle>,
+            Option<&Text3dLod>,
+        ),
+        Or<(
+            Changed<Text3d>,
+            Changed<Text3dStyle>,
+            Changed<Text3dBatched>,
+        )>,
+    >,
     mut commands: Commands,
     font_atlas: Res<FontAtlasSets>,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -307,32 +878,51 @@ pub fn create_shadow_caster_meshes_system(
     mut depth_only_materials: ResMut<Assets<crate::pipeline_material::DepthOnlyMaterial>>,
     fonts: Res<Assets<Font>>,
     config: Res<TextMeshPluginConfig>,
+    mut profile_cache: ResMut<GlyphProfileMeshCache>,
+    mut profile_materials: ResMut<GlyphProfileMaterials>,
+    mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut instance_meshes: ResMut<GlyphInstanceMeshes>,
+    mut material_cache: ResMut<GlyphMaterialCache>,
+    mut glyph_labels: ResMut<GlyphLabels>,
 ) {
-    for (entity, mut text_glyph_mesh) in query.iter_mut() {
-        let has_atlas_meshes = !text_glyph_mesh.meshes.is_empty();
-        let needs_glyph_profile = text_glyph_mesh.glyph_profile_mesh.is_none();
+    for (entity, mut text_glyph_mesh, config_override, batched, style, lod) in query.iter_mut() {
+        let style = style.unwrap_or(&DEFAULT_STYLE);
+        // Batched text is drawn by its batch, and text switching SDF tiers draws from other
+        // atlas pages; drop the atlas children it had before.
+        let tier_changed = text_glyph_mesh.atlas_tier != text_glyph_mesh.sdf_tier;
+        if (batched || tier_changed) && !text_glyph_mesh.child_entities.is_empty() {
+            for (_, child) in text_glyph_mesh.child_entities.drain() {
+                commands.entity(child).despawn();
+            }
+            text_glyph_mesh.meshes.clear();
+            text_glyph_mesh.glyph_instances.clear();
+            text_glyph_mesh.last_mesh_counts.clear();
+            text_glyph_mesh.atlas_generation = None;
+            text_glyph_mesh.material_key = None;
+            if batched {
+                glyph_labels.release(entity);
+            }
+        }
+        if tier_changed {
+            text_glyph_mesh.atlas_tier = text_glyph_mesh.sdf_tier;
+        }
 
-        // Only create atlas meshes if they don't exist yet
-        if !has_atlas_meshes {
+        // Atlas membership can only change when the glyphs (or the atlases backing
+        // them) change, both of which bump the generation. Only read through
+        // `text_glyph_mesh` until then so idle text isn't flagged as changed.
+        let generation = text_glyph_mesh.generation;
+        let needs_atlas_sync = !batched && text_glyph_mesh.atlas_generation != Some(generation);
+        let screen_sized = style.screen_size.is_some();
+        let billboard = style.billboard.is_some();
+        let needs_glyph_profile = text_glyph_mesh.glyph_profile_generation != Some(generation)
+            || text_glyph_mesh.glyph_profile_screen_sized != screen_sized
+            || text_glyph_mesh.glyph_profile_billboard != billboard
+            || text_glyph_mesh.glyph_profile_shadow_lod != text_glyph_mesh.shadow_lod;
+
+        if needs_atlas_sync {
             // Ensure text glyph atlas exists for the chosen font.
-            if let Some(data) = font_atlas.sets.get(&text_glyph_mesh.font.id()) {
-                info!(
-                    "Creating individual text character meshes for Text3d entity ({:?}) with text '{}'",
-                    entity,
-                    text_glyph_mesh
-                        .glyphs()
-                        .iter()
-                        .map(|glyph| glyph.character)
-                        .collect::<String>()
-                );
-                commands.entity(entity).insert((
-                    Visibility::default(),
-                    InheritedVisibility::default(),
-                    ViewVisibility::default(),
-                ));
-
-                // Create meshes needed to cast shadows for this Text3d (a group of characters).
-                let mut needed_atlases: HashSet<usize> = HashSet::new();
+            if let Some(data) = text_glyph_mesh.atlas_set(&font_atlas) {
+                // Group the glyphs by the atlas page that holds them.
                 let mut atlas_to_glyphs: HashMap<usize, Vec<char>> = HashMap::new();
                 // Collect missing glyph characters we haven't warned about yet so
                 // we can update `text_mesh` after finishing the iteration and
@@ -344,10 +934,9 @@ pub fn create_shadow_caster_meshes_system(
                             "Text3d ({:?}) glyph={} needs atlas={}",
                             entity, glyph.character, atlas_idx
                         );
-                        needed_atlases.insert(atlas_idx);
                         atlas_to_glyphs
                             .entry(atlas_idx)
-                            .or_insert(Vec::new())
+                            .or_default()
                             .push(glyph.character);
                     } else {
                         #[cfg(debug_assertions)]
@@ -372,36 +961,89 @@ pub fn create_shadow_caster_meshes_system(
                     text_glyph_mesh.logged_missing_glyphs.insert(code_point);
                 }
 
-                // Create meshes and child entities for each needed atlas.
-                for &i in needed_atlases.iter() {
+                // Drop meshes and child entities for atlases no longer referenced by any glyph.
+                // Their mesh and material assets are freed once the last handle is gone.
+                let stale_atlases: Vec<usize> = text_glyph_mesh
+                    .meshes
+                    .keys()
+                    .filter(|i| !atlas_to_glyphs.contains_key(i))
+                    .copied()
+                    .collect();
+                for i in stale_atlases {
+                    text_glyph_mesh.meshes.remove(&i);
+                    text_glyph_mesh.last_mesh_counts.remove(&i);
+                    text_glyph_mesh.glyph_instances.remove(&i);
+                    if let Some(child) = text_glyph_mesh.child_entities.remove(&i) {
+                        commands.entity(child).despawn();
+                        info!(
+                            "Despawned Mesh3d child entity={:?} for unused atlas {} of Text3d parent entity={:?}",
+                            child, i, entity
+                        );
+                    }
+                }
+
+                // Create meshes and child entities for each newly needed atlas.
+                let mut synced = true;
+                for (&i, atlas_glyphs) in atlas_to_glyphs.iter() {
                     if text_glyph_mesh.meshes.contains_key(&i) {
                         continue;
                     }
 
-                    let mesh = meshes.add(Mesh::new(
-                        PrimitiveTopology::TriangleList,
-                        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-                    ));
-                    // Insert handle into the Text3d so other systems can find it
-                    let mesh_clone = mesh.clone();
-                    text_glyph_mesh.meshes.insert(i, mesh_clone.clone());
-                    // Instrument: check the atlas texture presence before adding
-                    let atlas_texture = data.atlas_texture(i);
-                    if atlas_texture.is_none() {
+                    let Some(atlas_texture_handle) = data.atlas_texture(i) else {
                         info!(
                             "Texture for glyphs {:?} in atlas {} (Text3d entity {:?}, font_id={:?}) - will be generated next frame",
-                            atlas_to_glyphs.get(&i).unwrap_or(&vec![]),
+                            atlas_glyphs,
                             i,
                             entity,
                             text_glyph_mesh.font_id()
                         );
+                        // Retry the sync next frame.
+                        synced = false;
                         continue;
+                    };
+                    info!(
+                        "Creating text character mesh for atlas {} of Text3d entity ({:?}) with glyphs '{}'",
+                        i,
+                        entity,
+                        atlas_glyphs.iter().collect::<String>()
+                    );
+
+                    let instanced = text_glyph_mesh.glyph_instancing;
+                    let (mesh, glyph_instances) = if instanced {
+                        // Sized and filled once the quads are rebuilt.
+                        let glyph_instances = storage_buffers
+                            .add(ShaderStorageBuffer::from(vec![GlyphInstance::default()]));
+                        (instance_meshes.mesh(0, &mut meshes), glyph_instances)
+                    } else {
+                        let mesh = meshes.add(Mesh::new(
+                            PrimitiveTopology::TriangleList,
+                            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+                        ));
+                        (mesh, EMPTY_GLYPH_INSTANCES)
+                    };
+                    // Insert handle into the Text3d so other systems can find it
+                    text_glyph_mesh.meshes.insert(i, mesh.clone());
+                    // The new mesh is empty until the quads are rebuilt.
+                    text_glyph_mesh.mesh_generation = None;
+                    let glyph_material_handle = if instanced {
+                        materials.add(GlyphMaterial {
+                            glyph_instances: glyph_instances.clone(),
+                            instanced: true,
+                            ..style.material(atlas_texture_handle.clone(), data.range())
+                        })
+                    } else {
+                        material_cache.material(
+                            &atlas_texture_handle,
+                            style,
+                            data.range(),
+                            &mut materials,
+                        )
+                    };
+                    if instanced {
+                        text_glyph_mesh
+                            .glyph_instances
+                            .insert(i, (glyph_instances, glyph_material_handle.clone()));
                     }
-                    let atlas_texture_handle = atlas_texture.unwrap();
-                    let glyph_material_handle = materials.add(GlyphMaterial {
-                        params: crate::pipeline_material::GlyphMaterialUniform::default(),
-                        sdf_texture: atlas_texture_handle.clone(),
-                    });
 
                     debug!(
                         "Creating material for atlas {}: material_handle={:?}, atlas_texture_handle={:?}",
@@ -410,264 +1052,427 @@ pub fn create_shadow_caster_meshes_system(
 
                     let child = commands
                         .spawn((
-                            Mesh3d(mesh_clone),
+                            Mesh3d(mesh),
                             bevy::pbr::MeshMaterial3d(glyph_material_handle.clone()),
+                            glyph_labels.tag(entity, style),
                             bevy::prelude::Transform::IDENTITY,
                             bevy::prelude::Visibility::Inherited,
                             bevy::prelude::InheritedVisibility::default(),
                             ViewVisibility::default(),
-                            NotShadowCaster,
                         ))
                         .id();
+                    if screen_sized
+                        || text_glyph_mesh.shadow_lod == ShadowLod::Disabled
+                        || text_glyph_mesh.glyph_profile_render_mode
+                            != GlyphProfileRenderMode::SdfMasked
+                    {
+                        commands.entity(child).insert(NotShadowCaster);
+                    }
+                    if let Some(lod) = lod {
+                        commands.entity(child).insert(lod.visibility_range());
+                    }
 
                     commands.entity(entity).add_child(child);
                     text_glyph_mesh.child_entities.insert(i, child);
+                    // Restyling skips text whose materials were built for the same key.
+                    text_glyph_mesh.material_key = Some(style.key());
                     info!(
                         "Created Mesh3d child entity={:?} for Text3d parent entity={:?}",
                         child, entity
                     );
                 }
+                if synced {
+                    text_glyph_mesh.atlas_generation = Some(generation);
+                } else {
+                    text_glyph_mesh.set_changed();
+                }
             } else {
                 debug!(
                     "Font data not found for Text3d ({:?}) font_id={:?}. Will try again next frame.",
                     entity,
                     text_glyph_mesh.font_id()
                 );
+                text_glyph_mesh.set_changed();
                 continue;
             }
         }
 
-        // Create glyph profile mesh for shadow casting if needed
-        if needs_glyph_profile {
-            if let Some(data) = font_atlas.sets.get(&text_glyph_mesh.font.id()) {
-                info!(
-                    "Creating glyph profile mesh for Text3d ({:?}) with quality {:?}",
-                    entity, config.text_mesh_shadow_quality
-                );
-                let mut combined_mesh = Mesh::new(
-                    PrimitiveTopology::TriangleList,
-                    RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-                );
-                let mut vertices = Vec::new();
-                let mut indices = Vec::new();
-                let mut vertex_offset = 0u32;
+        // (Re)build the glyph profile mesh for shadow casting if it is missing or was
+        // built from glyphs that have since changed.
+        if !needs_glyph_profile {
+            continue;
+        }
+        text_glyph_mesh.glyph_profile_screen_sized = screen_sized;
+        text_glyph_mesh.glyph_profile_billboard = billboard;
+        let shadow_lod = text_glyph_mesh.shadow_lod;
+        text_glyph_mesh.glyph_profile_shadow_lod = shadow_lod;
+        // Only the atlas quads of `SdfMasked` text cast shadows. Screen sized text casts none,
+        // as its size only holds for the camera, and neither does text `Text3dLod` dropped
+        // the shadows of. Billboarded text turns per view, which the static profile can't
+        // follow, so only its `SdfMasked` quads cast shadows.
+        let no_shadows = screen_sized || shadow_lod == ShadowLod::Disabled;
+        let sdf_masked = !no_shadows
+            && text_glyph_mesh.glyph_profile_render_mode == GlyphProfileRenderMode::SdfMasked;
+        for &child in text_glyph_mesh.child_entities.values() {
+            if sdf_masked {
+                commands.entity(child).remove::<NotShadowCaster>();
+            } else {
+                commands.entity(child).insert(NotShadowCaster);
+            }
+        }
+        if sdf_masked || no_shadows || billboard {
+            if let Some(child) = text_glyph_mesh.glyph_profile_child_entity.take() {
+                commands.entity(child).despawn();
+            }
+            text_glyph_mesh.glyph_profile_mesh = None;
+            text_glyph_mesh.glyph_profile_child_mode = text_glyph_mesh.glyph_profile_render_mode;
+            text_glyph_mesh.glyph_profile_generation = Some(generation);
+            continue;
+        }
+        let (Some(data), Some(font)) = (
+            text_glyph_mesh.atlas_set(&font_atlas),
+            fonts.get(&text_glyph_mesh.font),
+        ) else {
+            // Retry once the font is loaded.
+            text_glyph_mesh.set_changed();
+            continue;
+        };
+        let mut config = TextMeshConfigOverride::resolve(config_override, &config);
+        config.text_mesh_shadow_quality = shadow_lod.quality(config.text_mesh_shadow_quality);
+        info!(
+            "Creating glyph profile mesh for Text3d ({:?}) with quality {:?}",
+            entity, config.text_mesh_shadow_quality
+        );
+        let combined_mesh = build_glyph_profile_mesh(
+            &text_glyph_mesh.glyphs,
+            text_glyph_mesh.font.id(),
+            font,
+            data,
+            &config,
+            &mut profile_cache,
+        );
+        text_glyph_mesh.glyph_profile_generation = Some(generation);
 
-                for glyph in text_glyph_mesh.glyphs.iter() {
-                    if let Some(info) = data.glyph_info(glyph.character) {
-                        if let Some(glyph_profile_mesh) =
-                            fonts.get(&text_glyph_mesh.font).and_then(|font_handle| {
-                                font_handle.generate_glyph_profile_mesh_with_tolerance(
-                                    info.id,
-                                    glyph.character,
-                                    config.text_mesh_shadow_quality.tolerance(),
-                                )
-                            })
-                        {
-                            // Transform vertices by glyph position
-                            let offset = glyph.position.min + info.offset;
-                            if let (
-                                Some(VertexAttributeValues::Float32x3(positions)),
-                                Some(Indices::U32(mesh_indices)),
-                            ) = (
-                                glyph_profile_mesh.attribute(Mesh::ATTRIBUTE_POSITION),
-                                glyph_profile_mesh.indices(),
-                            ) {
-                                for pos in positions {
-                                    vertices.push([
-                                        pos[0] * config.font_scale.x + offset.x,
-                                        pos[1] * config.font_scale.y + offset.y,
-                                        pos[2],
-                                    ]);
-                                }
-                                for idx in mesh_indices {
-                                    indices.push(idx + vertex_offset);
-                                }
-                                vertex_offset += positions.len() as u32;
-                            }
-                        }
-                    }
+        let Some(combined_mesh) = combined_mesh else {
+            // Nothing left to cast a shadow (e.g. only whitespace); drop the old profile.
+            if let Some(child) = text_glyph_mesh.glyph_profile_child_entity.take() {
+                commands.entity(child).despawn();
+                debug!(
+                    "Despawned glyph profile child entity={:?} for Text3d(entity={:?}) without profile geometry",
+                    child, entity
+                );
+            }
+            text_glyph_mesh.glyph_profile_mesh = None;
+            continue;
+        };
+        let vert_count = combined_mesh.count_vertices();
+
+        // Overwrite the existing mesh asset in place so the child entity, and any caller
+        // holding `glyph_profile_mesh_handle()`, pick up the new geometry.
+        let existing_mesh = text_glyph_mesh
+            .glyph_profile_mesh
+            .clone()
+            .filter(|handle| meshes.contains(handle));
+        let glyph_profile_mesh_handle = match existing_mesh {
+            Some(handle) => {
+                if let Some(mesh) = meshes.get_mut(&handle) {
+                    *mesh = combined_mesh;
                 }
+                handle
+            }
+            None => meshes.add(combined_mesh),
+        };
+        text_glyph_mesh.glyph_profile_mesh = Some(glyph_profile_mesh_handle.clone());
+        info!(
+            "Created shadow caster mesh with {} vertices for Text3d entity ({:?}) with text '{}'",
+            vert_count,
+            entity,
+            text_glyph_mesh
+                .glyphs()
+                .iter()
+                .map(|glyph| glyph.character)
+                .collect::<String>()
+        );
 
-                if !vertices.is_empty() {
-                    // Insert positions and flat normals (Z+) so pipelines that expect normals
-                    // (like StandardMaterial for shadow passes) have valid vertex inputs.
-                    let vert_count = vertices.len();
-                    combined_mesh.insert_attribute(
-                        Mesh::ATTRIBUTE_POSITION,
-                        VertexAttributeValues::Float32x3(vertices),
-                    );
-                    combined_mesh.insert_attribute(
-                        Mesh::ATTRIBUTE_NORMAL,
-                        VertexAttributeValues::Float32x3(vec![[0.0, 0.0, 1.0]; vert_count]),
-                    );
-                    combined_mesh.insert_indices(Indices::U32(indices));
-                    let glyph_profile_mesh_handle = meshes.add(combined_mesh);
-                    text_glyph_mesh.glyph_profile_mesh = Some(glyph_profile_mesh_handle.clone());
-                    info!(
-                        "Created shadow caster mesh with {} vertices for Text3d entity ({:?}) with text '{}'",
-                        vert_count,
-                        entity,
-                        text_glyph_mesh
-                            .glyphs()
-                            .iter()
-                            .map(|glyph| glyph.character)
-                            .collect::<String>()
-                    );
+        if let Some(child) = text_glyph_mesh.glyph_profile_child_entity {
+            if text_glyph_mesh.glyph_profile_child_mode == text_glyph_mesh.glyph_profile_render_mode
+            {
+                // Reuse the existing child; it only needs pointing at the mesh if the
+                // handle was replaced.
+                commands
+                    .entity(child)
+                    .insert(Mesh3d(glyph_profile_mesh_handle.clone()));
+                continue;
+            }
+            // The render mode changed, so the child needs a different material.
+            commands.entity(child).despawn();
+            text_glyph_mesh.glyph_profile_child_entity = None;
+        }
+        text_glyph_mesh.glyph_profile_child_mode = text_glyph_mesh.glyph_profile_render_mode;
 
-                    // Create child entity for glyph profile mesh based on the configured render mode
-                    match text_glyph_mesh.glyph_profile_render_mode {
-                        GlyphProfileRenderMode::None => {
-                            // No glyph profile rendering
-                        }
-                        GlyphProfileRenderMode::DepthOnly => {
-                            let depth_mat = depth_only_materials
-                                .add(crate::pipeline_material::DepthOnlyMaterial {});
-                            let glyph_profile_child = commands
-                                .spawn((
-                                    Mesh3d(glyph_profile_mesh_handle.clone()),
-                                    MeshMaterial3d(depth_mat),
-                                    Transform::IDENTITY,
-                                    Visibility::Hidden,
-                                    InheritedVisibility::default(),
-                                    ViewVisibility::default(),
-                                    OnlyShadowCaster,
-                                ))
-                                .id();
-                            commands.entity(entity).add_child(glyph_profile_child);
-                            text_glyph_mesh.glyph_profile_child_entity = Some(glyph_profile_child);
-                            info!(
-                                "Created depth-only glyph profile child entity={:?} for Text3d(entity={:?})",
-                                glyph_profile_child, entity
-                            );
-                        }
-                        GlyphProfileRenderMode::Visible => {
-                            let debug_mat = standard_materials.add(StandardMaterial {
-                                base_color: Color::BLACK,
-                                alpha_mode: AlphaMode::Opaque,
-                                ..Default::default()
-                            });
-                            let visible_glyph_profile = commands
-                                .spawn((
-                                    Mesh3d(glyph_profile_mesh_handle.clone()),
-                                    MeshMaterial3d(debug_mat),
-                                    Transform::IDENTITY,
-                                    Visibility::Inherited,
-                                    InheritedVisibility::default(),
-                                    ViewVisibility::default(),
-                                ))
-                                .id();
-                            commands.entity(entity).add_child(visible_glyph_profile);
-                            text_glyph_mesh.glyph_profile_child_entity =
-                                Some(visible_glyph_profile);
-                            info!(
-                                "Spawned visible debug glyph profile child={:?}",
-                                visible_glyph_profile
-                            );
-                        }
-                    }
+        // Create child entity for glyph profile mesh based on the configured render mode
+        match text_glyph_mesh.glyph_profile_render_mode {
+            GlyphProfileRenderMode::None | GlyphProfileRenderMode::SdfMasked => {
+                // No glyph profile rendering
+            }
+            GlyphProfileRenderMode::DepthOnly => {
+                let depth_mat = profile_materials.depth_only(&mut depth_only_materials);
+                let glyph_profile_child = commands
+                    .spawn((
+                        Mesh3d(glyph_profile_mesh_handle.clone()),
+                        MeshMaterial3d(depth_mat),
+                        Transform::IDENTITY,
+                        Visibility::Hidden,
+                        InheritedVisibility::default(),
+                        ViewVisibility::default(),
+                        OnlyShadowCaster,
+                    ))
+                    .id();
+                if let Some(lod) = lod {
+                    commands
+                        .entity(glyph_profile_child)
+                        .insert(lod.visibility_range());
+                }
+                commands.entity(entity).add_child(glyph_profile_child);
+                text_glyph_mesh.glyph_profile_child_entity = Some(glyph_profile_child);
+                info!(
+                    "Created depth-only glyph profile child entity={:?} for Text3d(entity={:?})",
+                    glyph_profile_child, entity
+                );
+            }
+            GlyphProfileRenderMode::Visible => {
+                let debug_mat = profile_materials.visible(&mut standard_materials);
+                let visible_glyph_profile = commands
+                    .spawn((
+                        Mesh3d(glyph_profile_mesh_handle.clone()),
+                        MeshMaterial3d(debug_mat),
+                        Transform::IDENTITY,
+                        Visibility::Inherited,
+                        InheritedVisibility::default(),
+                        ViewVisibility::default(),
+                    ))
+                    .id();
+                if let Some(lod) = lod {
+                    commands
+                        .entity(visible_glyph_profile)
+                        .insert(lod.visibility_range());
                 }
+                commands.entity(entity).add_child(visible_glyph_profile);
+                text_glyph_mesh.glyph_profile_child_entity = Some(visible_glyph_profile);
+                info!(
+                    "Spawned visible debug glyph profile child={:?}",
+                    visible_glyph_profile
+                );
             }
         }
     }
 }
 
-/// System that updates atlas mesh geometry for Text3d entities.
-/// Rebuilds mesh geometry when glyphs change, using change detection to avoid unnecessary work.
-pub fn update_atlas_meshes_system(
-    mut query: Query<(Entity, &mut Text3d)>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    font_atlas: Res<FontAtlasSets>,
-    config: Res<TextMeshPluginConfig>,
-) {
-    debug!("Running update_atlas_mesh system");
-    for (entity, mut text_mesh) in query.iter_mut() {
-        debug!("Processing Text3d entity: {:?}", entity);
-        let Some(data) = font_atlas.sets.get(&text_mesh.font.id()) else {
+/// Combines the tessellated profiles of `glyphs` into a single mesh positioned like the
+/// SDF quads. Returns `None` if none of the glyphs produce any geometry.
+fn build_glyph_profile_mesh(
+    glyphs: &[Glyph],
+    font_id: AssetId<Font>,
+    font: &Font,
+    data: &FontAtlasSet,
+    config: &TextMeshPluginConfig,
+    profile_cache: &mut GlyphProfileMeshCache,
+) -> Option<Mesh> {
+    let quality = config.text_mesh_shadow_quality;
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut vertex_offset = 0u32;
+
+    for glyph in glyphs.iter() {
+        let Some(info) = data.glyph_info(glyph.character) else {
             continue;
         };
-
-        // Compute hash of current glyph data for change detection
-        let mut hasher = DefaultHasher::new();
-        for glyph in text_mesh.glyphs.iter() {
-            glyph.character.hash(&mut hasher);
-            glyph.position.min.x.to_bits().hash(&mut hasher);
-            glyph.position.min.y.to_bits().hash(&mut hasher);
-            glyph.position.max.x.to_bits().hash(&mut hasher);
-            glyph.position.max.y.to_bits().hash(&mut hasher);
-            glyph.color[0].to_bits().hash(&mut hasher);
-            glyph.color[1].to_bits().hash(&mut hasher);
-            glyph.color[2].to_bits().hash(&mut hasher);
-            glyph.color[3].to_bits().hash(&mut hasher);
-        }
-        let current_hash = hasher.finish();
-
-        // Skip mesh rebuild if glyphs haven't changed and meshes already exist
-        // Unless the user has changed the tessellation quality, in which case
-        // we need to rebuild the meshes.
-        if text_mesh.glyphs_hash == Some(current_hash) && !text_mesh.meshes.is_empty() {
-            debug!(
-                "Text3d ({:?}) glyphs unchanged, skipping mesh rebuild",
-                entity
-            );
+        let Some(glyph_profile_mesh) = profile_cache
+            .meshes
+            .entry((font_id, glyph.character, quality))
+            .or_insert_with(|| {
+                font.generate_glyph_profile_mesh_with_tolerance(
+                    info.id,
+                    glyph.character,
+                    quality.tolerance(),
+                )
+            })
+            .as_ref()
+        else {
             continue;
-        }
-
-        // Update cached hash
-        text_mesh.glyphs_hash = Some(current_hash);
+        };
 
-        // Build positioned glyphs for those glyphs that have atlas UVs.
-        let mut positioned: Vec<PositionedGlyph> = Vec::new();
-        // Because we need to mutate `text_mesh.logged_missing_glyphs`, collect
-        // newly-missing glyphs first to avoid mutable/immutable borrow conflicts.
-        let mut newly_missing: Vec<char> = Vec::new();
-        for glyph in text_mesh.glyphs.iter() {
-            let info_opt = data.glyph_info(glyph.character);
-            if info_opt.is_none() {
-                info!(
-                    "Text3d ({:?}) for ({}) not ready; will be available in a future frame once atlas generation completes",
-                    entity, glyph.character
-                );
-                continue;
+        // Map vertices into the same rect as the glyph's SDF quad
+        let quad = glyph.quad_rect(info, config.font_scale.truncate());
+        if let (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(Indices::U32(mesh_indices)),
+        ) = (
+            glyph_profile_mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+            glyph_profile_mesh.indices(),
+        ) {
+            for pos in positions {
+                let point = Glyph::profile_point(&quad, info, Vec2::new(pos[0], pos[1]));
+                let point = glyph.transform_point(&quad, point);
+                vertices.push([point.x, point.y, point.z + pos[2]]);
             }
-            let info = info_opt.unwrap();
-
-            match data.atlas(glyph.character) {
-                Some(atlas_idx) => {
-                    if let Some(uv_rect) = data.find_glyph_rect(info.id) {
-                        let min = glyph.position.min + info.offset;
-                        let size_scaled = info.size * config.font_scale.truncate();
-                        let pos_rect = Rect::from_corners(min, min + size_scaled);
-                        positioned.push(PositionedGlyph {
-                            position: pos_rect,
-                            uv: uv_rect,
-                            index: atlas_idx,
-                            color: glyph.color,
-                        });
-                    } else {
-                        warn!(
-                            "Text3d ({:?}) glyph={} has atlas entry but no uv rect; skipping quad",
-                            entity, glyph.character
-                        );
-                    }
-                }
-                None => {
-                    #[cfg(debug_assertions)]
-                    if !text_mesh.logged_missing_glyphs.contains(&glyph.character) {
-                        newly_missing.push(glyph.character);
-                    }
-                    #[cfg(not(debug_assertions))]
-                    {
-                        newly_missing.push(glyph.character);
-                    }
-                }
+            for idx in mesh_indices {
+                indices.push(idx + vertex_offset);
             }
+            vertex_offset += positions.len() as u32;
         }
+    }
 
-        for c in newly_missing.into_iter() {
-            warn!("Text3d ({:?}) glyph={} has no atlas!", entity, c);
-            #[cfg(debug_assertions)]
-            text_mesh.logged_missing_glyphs.insert(c);
+    if vertices.is_empty() {
+        return None;
+    }
+
+    // Insert positions and flat normals (Z+) so pipelines that expect normals
+    // (like StandardMaterial for shadow passes) have valid vertex inputs.
+    let vert_count = vertices.len();
+    let mut combined_mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    combined_mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        VertexAttributeValues::Float32x3(vertices),
+    );
+    combined_mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        VertexAttributeValues::Float32x3(vec![[0.0, 0.0, 1.0]; vert_count]),
+    );
+    combined_mesh.insert_indices(Indices::U32(indices));
+    Some(combined_mesh)
+}
+
+/// Caches tessellated glyph profiles so rebuilding a [`Text3d`]'s shadow caster after
+/// `set_glyphs` only re-positions geometry instead of re-tessellating every glyph.
+#[derive(Default, Resource)]
+pub struct GlyphProfileMeshCache {
+    meshes: HashMap<(AssetId<Font>, char, GlyphTessellationQuality), Option<Mesh>>,
+}
+
+/// Materials shared by every glyph profile child, created the first time one is spawned.
+#[derive(Default, Resource)]
+pub struct GlyphProfileMaterials {
+    depth_only: Option<Handle<crate::pipeline_material::DepthOnlyMaterial>>,
+    visible: Option<Handle<StandardMaterial>>,
+}
+
+impl GlyphProfileMaterials {
+    /// Returns the material of depth-only profiles.
+    pub(crate) fn depth_only(
+        &mut self,
+        materials: &mut Assets<crate::pipeline_material::DepthOnlyMaterial>,
+    ) -> Handle<crate::pipeline_material::DepthOnlyMaterial> {
+        self.depth_only
+            .get_or_insert_with(|| materials.add(crate::pipeline_material::DepthOnlyMaterial {}))
+            .clone()
+    }
+
+    /// Returns the black debug material of visible profiles.
+    pub(crate) fn visible(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        self.visible
+            .get_or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: Color::BLACK,
+                    alpha_mode: AlphaMode::Opaque,
+                    ..Default::default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Meshes shared by instanced `Text3d`, keyed by glyph capacity.
+///
+/// The vertices only carry their own index; `glyph_vertex` in `text3d_glyph.wgsl` turns every
+/// six of them into the quad of one glyph record and collapses those past the end of the
+/// instance buffer.
+/// Capacities are rounded up to a power of two so text of similar length shares a mesh.
+#[derive(Default, Resource)]
+pub struct GlyphInstanceMeshes {
+    meshes: HashMap<usize, Handle<Mesh>>,
+}
+
+impl GlyphInstanceMeshes {
+    /// Smallest capacity handed out, so short labels don't each get their own mesh.
+    const MIN_CAPACITY: usize = 16;
+
+    /// Returns a mesh with room for at least `glyph_count` glyphs.
+    pub(crate) fn mesh(&mut self, glyph_count: usize, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        let capacity = glyph_count.next_power_of_two().max(Self::MIN_CAPACITY);
+        self.meshes
+            .entry(capacity)
+            .or_insert_with(|| {
+                let mut mesh = Mesh::new(
+                    PrimitiveTopology::TriangleList,
+                    RenderAssetUsages::RENDER_WORLD,
+                );
+                mesh.insert_attribute(
+                    ATTRIBUTE_GLYPH_VERTEX_INDEX,
+                    VertexAttributeValues::Uint32((0..capacity as u32 * 6).collect()),
+                );
+                meshes.add(mesh)
+            })
+            .clone()
+    }
+}
+
+/// System that updates atlas mesh geometry for Text3d entities.
+/// Rebuilds mesh geometry when glyphs change, using change detection to avoid unnecessary work.
+///
+/// Only `Text3d` components changed since the last run are visited, and of those only the
+/// ones whose generation moved past the one their quads were built for are rebuilt.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_atlas_meshes_system(
+    mut query: Query<
+        (
+            Entity,
+            &mut Text3d,
+            Option<&TextMeshConfigOverride>,
+            Option<&Text3dStyle>,
+        ),
+        (Changed<Text3d>, Without<Text3dBatched>),
+    >,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GlyphMaterial>>,
+    mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut instance_meshes: ResMut<GlyphInstanceMeshes>,
+    font_atlas: Res<FontAtlasSets>,
+    config: Res<TextMeshPluginConfig>,
+) {
+    debug!("Running update_atlas_mesh system");
+    for (entity, mut text_mesh, config_override, style) in query.iter_mut() {
+        debug!("Processing Text3d entity: {:?}", entity);
+        let Some(data) = text_mesh.atlas_set(&font_atlas) else {
+            continue;
+        };
+
+        let config = TextMeshConfigOverride::resolve(config_override, &config);
+
+        // Skip mesh rebuild if the quads were already built for the current generation;
+        // the change may have come from this system or from an unrelated mutation.
+        let generation = text_mesh.generation;
+        if text_mesh.mesh_generation == Some(generation) {
+            debug!(
+                "Text3d ({:?}) glyphs unchanged, skipping mesh rebuild",
+                entity
+            );
+            continue;
         }
+        text_mesh.mesh_generation = Some(generation);
+
+        let padding = style.map_or(0, |style| style.padding(data.range()));
+        let positioned =
+            text_mesh.position_glyphs(entity, data, config.font_scale.truncate(), padding);
+        let bounds = QuadBounds::of(style);
+        text_mesh.quad_bounds = bounds;
 
         // Iterate each atlas mesh and write geometry; only log counts when
         // they change to avoid repeating identical messages every frame.
@@ -678,22 +1483,80 @@ pub fn update_atlas_meshes_system(
             .collect();
 
         for (index, mesh_handle) in atlas_pairs.into_iter() {
+            let atlas_glyphs: Vec<&PositionedGlyph> =
+                positioned.iter().filter(|pg| pg.index == index).collect();
+            let child = text_mesh.child_entities.get(&index).copied();
+            // Bevy only computes bounds once per entity, and not at all for meshes without
+            // `Mesh::ATTRIBUTE_POSITION`, so keep the culling bounds in sync ourselves.
+            if let Some(child) = child {
+                let mut aabb = Aabb::enclosing(
+                    atlas_glyphs
+                        .iter()
+                        .flat_map(|pg| quad_corners(&pg.position, pg.transform.as_ref())),
+                )
+                .unwrap_or_default();
+                if bounds == QuadBounds::Billboard {
+                    aabb = billboard_bounds(aabb);
+                }
+                commands.entity(child).insert(aabb);
+                if bounds == QuadBounds::Unbounded {
+                    commands.entity(child).insert(NoFrustumCulling);
+                } else {
+                    commands.entity(child).remove::<NoFrustumCulling>();
+                }
+            }
+
+            // Instanced text only rewrites its glyph records, swapping to a larger shared
+            // mesh when the glyph count outgrows the current one.
+            if let Some((buffer, material)) = text_mesh.glyph_instances.get(&index).cloned() {
+                upload_glyph_records(
+                    atlas_glyphs.iter().map(|pg| pg.instance()).collect(),
+                    &buffer,
+                    &material,
+                    &mut storage_buffers,
+                    &mut materials,
+                );
+
+                let shared_mesh = instance_meshes.mesh(atlas_glyphs.len(), &mut meshes);
+                if shared_mesh != mesh_handle {
+                    text_mesh.meshes.insert(index, shared_mesh.clone());
+                    if let Some(child) = child {
+                        commands.entity(child).insert(Mesh3d(shared_mesh));
+                    }
+                }
+                debug!(
+                    "Text3d ({:?}) atlas={} -> glyph instances={}",
+                    entity,
+                    index,
+                    atlas_glyphs.len()
+                );
+                continue;
+            }
+
             if let Some(mesh) = meshes.get_mut(&mesh_handle) {
-                let mut builder = Text3dBuilder::new(mesh);
-                for pg in positioned.iter().filter(|pg| pg.index == index) {
-                    builder.append_glyph(&pg.position, &pg.uv, &pg.color);
+                let emissive = atlas_glyphs.iter().any(|pg| pg.emissive != 0.0);
+                let mut builder =
+                    Text3dBuilder::new(mesh, config.standard_position_attribute, emissive);
+                for pg in atlas_glyphs.iter() {
+                    builder.append_glyph(
+                        &pg.position,
+                        pg.transform.as_ref(),
+                        &pg.uv,
+                        &pg.color,
+                        pg.emissive,
+                    );
                 }
 
                 let pos_count = match mesh.attribute(ATTRIBUTE_POSITION) {
-                    Some(VertexAttributeValues::Float32x2(v)) => v.len(),
+                    Some(VertexAttributeValues::Float32x3(v)) => v.len(),
                     _ => 0,
                 };
-                let uv_count = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
-                    Some(VertexAttributeValues::Float32x2(v)) => v.len(),
+                let uv_count = match mesh.attribute(ATTRIBUTE_UV) {
+                    Some(VertexAttributeValues::Unorm16x2(v)) => v.len(),
                     _ => 0,
                 };
-                let color_count = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
-                    Some(VertexAttributeValues::Float32x4(v)) => v.len(),
+                let color_count = match mesh.attribute(ATTRIBUTE_COLOR) {
+                    Some(VertexAttributeValues::Unorm8x4(v)) => v.len(),
                     _ => 0,
                 };
                 let index_count = match mesh.indices() {
@@ -725,13 +1588,12 @@ pub fn update_atlas_meshes_system(
                     );
                 }
 
-                let child_opt = text_mesh.child_entities.get(&index).cloned();
-                if child_opt.is_none() {
+                if child.is_none() {
                     warn!(
                         "Text3d ({:?}) atlas={} has no child entity yet",
                         entity, index
                     );
-                } else if should_log && let Some(child) = child_opt {
+                } else if should_log && let Some(child) = child {
                     debug!(
                         "Text3d ({:?}) atlas={} child_entity={:?}",
                         entity, index, child
@@ -744,115 +1606,106 @@ pub fn update_atlas_meshes_system(
 
 /// Helper for building mesh geometry for text glyphs.
 /// Clears existing mesh data and appends glyph quads with proper vertex attributes.
+///
+/// Vertices use a packed layout: `Float32x3` positions, `Unorm16x2` UVs, `Unorm8x4` colors
+/// and `Unorm16x2` glyph coordinates, 24 bytes per vertex. Bevy's `Mesh::ATTRIBUTE_POSITION` is only written when
+/// `standard_position` is set, and a `Float32` emissive intensity only when `emissive` is.
 struct Text3dBuilder<'a> {
     index: u32,
     mesh: &'a mut Mesh,
 }
 
-// if we want to move text Z-direction relative to the other text, we may need f32x3 here..
-// pub const ATTRIBUTE_TEXT_POSITION: MeshVertexAttribute =
-//     MeshVertexAttribute::new("Text_Position", 988540917, VertexFormat::Float32x2);
-
 impl<'a> Text3dBuilder<'a> {
     /// Creates a new Text3dBuilder, clearing all existing mesh attributes and indices.
     /// Ensures the mesh has the required vertex attribute arrays initialized.
-    fn new(mesh: &'a mut Mesh) -> Self {
-        if !mesh.contains_attribute(ATTRIBUTE_POSITION) {
-            mesh.insert_attribute(ATTRIBUTE_POSITION, VertexAttributeValues::Float32x2(vec![]));
-        }
-        // Ensure the standard 3-component position attribute exists too so
-        // that attaching Bevy's `StandardMaterial` (which expects a
-        // Float32x3 `POSITION`) won't fail pipeline specialization.
-        if !mesh.contains_attribute(Mesh::ATTRIBUTE_POSITION) {
+    fn new(mesh: &'a mut Mesh, standard_position: bool, emissive: bool) -> Self {
+        mesh.insert_attribute(ATTRIBUTE_POSITION, VertexAttributeValues::Float32x3(vec![]));
+        mesh.insert_attribute(ATTRIBUTE_UV, VertexAttributeValues::Unorm16x2(vec![]));
+        mesh.insert_attribute(ATTRIBUTE_COLOR, VertexAttributeValues::Unorm8x4(vec![]));
+        mesh.insert_attribute(
+            ATTRIBUTE_GLYPH_COORD,
+            VertexAttributeValues::Unorm16x2(vec![]),
+        );
+        // The standard 3-component position lets Bevy's `StandardMaterial` (which expects a
+        // Float32x3 `POSITION`) specialize for the mesh, at the cost of duplicating positions.
+        if standard_position {
             mesh.insert_attribute(
                 Mesh::ATTRIBUTE_POSITION,
                 VertexAttributeValues::Float32x3(vec![]),
             );
+        } else {
+            mesh.remove_attribute(Mesh::ATTRIBUTE_POSITION);
         }
-
-        if !mesh.contains_attribute(Mesh::ATTRIBUTE_UV_0) {
-            mesh.insert_attribute(
-                Mesh::ATTRIBUTE_UV_0,
-                VertexAttributeValues::Float32x2(vec![]),
-            );
-        }
-        // FIXME: 4 vertices with f32x4 for color seems overkill for a single color glyph
-        if !mesh.contains_attribute(Mesh::ATTRIBUTE_COLOR) {
+        // Only meshes with emissive glyphs pay for the extra attribute.
+        if emissive {
             mesh.insert_attribute(
-                Mesh::ATTRIBUTE_COLOR,
-                VertexAttributeValues::Float32x4(vec![]),
+                ATTRIBUTE_GLYPH_EMISSIVE,
+                VertexAttributeValues::Float32(vec![]),
             );
+        } else {
+            mesh.remove_attribute(ATTRIBUTE_GLYPH_EMISSIVE);
         }
-        if mesh.indices().is_none() {
-            mesh.insert_indices(Indices::U32(vec![]));
-        }
-
-        if let Some(VertexAttributeValues::Float32x2(vertices)) =
-            mesh.attribute_mut(ATTRIBUTE_POSITION)
-        {
-            vertices.clear();
-        }
-        if let Some(VertexAttributeValues::Float32x3(std_positions)) =
-            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
-        {
-            std_positions.clear();
-        }
-        if let Some(VertexAttributeValues::Float32x2(uvs)) =
-            mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0)
-        {
-            uvs.clear();
-        }
-        if let Some(VertexAttributeValues::Float32x4(colors)) =
-            mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
-        {
-            colors.clear();
-        }
-        if let Some(Indices::U32(indices)) = mesh.indices_mut() {
-            indices.clear();
-        }
+        mesh.insert_indices(Indices::U32(vec![]));
 
         Self { index: 0, mesh }
     }
 
     /// Appends a glyph quad to the mesh with the specified position, UV coordinates, and color.
     /// Creates 4 vertices and 6 indices (2 triangles) for the glyph quad.
-    fn append_glyph(&mut self, position: &Rect, uv: &Rect, color: &[f32; 4]) {
-        if let Some(VertexAttributeValues::Float32x2(vertices)) =
+    /// The corners are transformed around the quad's center if a transform is given.
+    fn append_glyph(
+        &mut self,
+        position: &Rect,
+        transform: Option<&GlyphTransform>,
+        uv: &Rect,
+        color: &[f32; 4],
+        emissive: f32,
+    ) {
+        let corners = quad_corners(position, transform).map(|corner| corner.to_array());
+
+        if let Some(VertexAttributeValues::Float32x3(vertices)) =
             self.mesh.attribute_mut(ATTRIBUTE_POSITION)
         {
-            let rect = *position;
-            vertices.push([rect.min.x, rect.min.y]);
-            vertices.push([rect.max.x, rect.min.y]);
-            vertices.push([rect.max.x, rect.max.y]);
-            vertices.push([rect.min.x, rect.max.y]);
+            vertices.extend(corners);
         }
 
-        // Also write a 3-component POSITION with z=0 for compatibility with
-        // standard Bevy materials / PBR pipelines.
         if let Some(VertexAttributeValues::Float32x3(std_positions)) =
             self.mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
         {
-            let rect = *position;
-            std_positions.push([rect.min.x, rect.min.y, 0.0]);
-            std_positions.push([rect.max.x, rect.min.y, 0.0]);
-            std_positions.push([rect.max.x, rect.max.y, 0.0]);
-            std_positions.push([rect.min.x, rect.max.y, 0.0]);
+            std_positions.extend(corners);
         }
 
-        if let Some(VertexAttributeValues::Float32x2(uvs)) =
-            self.mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0)
-        {
+        if let Some(VertexAttributeValues::Unorm16x2(uvs)) = self.mesh.attribute_mut(ATTRIBUTE_UV) {
             let rect = *uv;
-            uvs.push([rect.min.x, rect.min.y]);
-            uvs.push([rect.max.x, rect.min.y]);
-            uvs.push([rect.max.x, rect.max.y]);
-            uvs.push([rect.min.x, rect.max.y]);
+            uvs.extend(
+                [
+                    [rect.min.x, rect.min.y],
+                    [rect.max.x, rect.min.y],
+                    [rect.max.x, rect.max.y],
+                    [rect.min.x, rect.max.y],
+                ]
+                .map(|uv| uv.map(|c| (c.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16)),
+            );
         }
 
-        if let Some(VertexAttributeValues::Float32x4(colors)) =
-            self.mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+        if let Some(VertexAttributeValues::Unorm8x4(colors)) =
+            self.mesh.attribute_mut(ATTRIBUTE_COLOR)
         {
-            colors.extend([*color; 4]); // FIXME: this wastes a ton of memory..
+            let color = color.map(|c| (c.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8);
+            colors.extend([color; 4]);
+        }
+
+        if let Some(VertexAttributeValues::Unorm16x2(coords)) =
+            self.mesh.attribute_mut(ATTRIBUTE_GLYPH_COORD)
+        {
+            let (min, max) = (0, u16::MAX);
+            coords.extend([[min, min], [max, min], [max, max], [min, max]]);
+        }
+
+        if let Some(VertexAttributeValues::Float32(emissives)) =
+            self.mesh.attribute_mut(ATTRIBUTE_GLYPH_EMISSIVE)
+        {
+            emissives.extend([emissive; 4]);
         }
 
         if let Some(Indices::U32(indices)) = self.mesh.indices_mut() {
@@ -873,6 +1726,9 @@ pub struct TextMeshPluginConfig {
     pub text_mesh_shadow_quality: GlyphTessellationQuality,
     /// Global scale applied to all text fonts.
     pub font_scale: Vec3,
+    /// Also writes Bevy's `Mesh::ATTRIBUTE_POSITION` into atlas meshes, duplicating the
+    /// glyph positions, so they can be drawn with Bevy's standard materials.
+    pub standard_position_attribute: bool,
 }
 
 impl Default for TextMeshPluginConfig {
@@ -880,6 +1736,67 @@ impl Default for TextMeshPluginConfig {
         Self {
             text_mesh_shadow_quality: GlyphTessellationQuality::High,
             font_scale: Vec3::ONE,
+            standard_position_attribute: false,
+        }
+    }
+}
+
+/// Per-entity overrides for [`TextMeshPluginConfig`].
+/// Fields left as `None` fall back to the global configuration resource.
+#[derive(Component, Clone, Debug, Default)]
+pub struct TextMeshConfigOverride {
+    /// Overrides [`TextMeshPluginConfig::text_mesh_shadow_quality`] for this entity.
+    pub text_mesh_shadow_quality: Option<GlyphTessellationQuality>,
+    /// Overrides [`TextMeshPluginConfig::font_scale`] for this entity.
+    pub font_scale: Option<Vec3>,
+}
+
+impl TextMeshConfigOverride {
+    /// Returns the configuration to use for an entity with the given (optional) override.
+    pub fn resolve(
+        config_override: Option<&Self>,
+        config: &TextMeshPluginConfig,
+    ) -> TextMeshPluginConfig {
+        let Some(config_override) = config_override else {
+            return config.clone();
+        };
+        TextMeshPluginConfig {
+            text_mesh_shadow_quality: config_override
+                .text_mesh_shadow_quality
+                .unwrap_or(config.text_mesh_shadow_quality),
+            font_scale: config_override.font_scale.unwrap_or(config.font_scale),
+            standard_position_attribute: config.standard_position_attribute,
+        }
+    }
+}
+
+/// System that rebuilds quads and glyph profiles when [`TextMeshPluginConfig`] or an
+/// entity's [`TextMeshConfigOverride`] changes at runtime.
+pub fn apply_text_mesh_config_changes_system(
+    config: Res<TextMeshPluginConfig>,
+    mut query: Query<&mut Text3d>,
+    changed_overrides: Query<Entity, (With<Text3d>, Changed<TextMeshConfigOverride>)>,
+    mut removed_overrides: RemovedComponents<TextMeshConfigOverride>,
+) {
+    if config.is_changed() && !config.is_added() {
+        info!(
+            "TextMeshPluginConfig changed (quality {:?}, font_scale {:?}); rebuilding all Text3d meshes",
+            config.text_mesh_shadow_quality, config.font_scale
+        );
+        for mut text_mesh in query.iter_mut() {
+            text_mesh.mark_meshes_stale();
+        }
+        removed_overrides.clear();
+        return;
+    }
+
+    for entity in changed_overrides.iter().chain(removed_overrides.read()) {
+        if let Ok(mut text_mesh) = query.get_mut(entity) {
+            debug!(
+                "TextMeshConfigOverride changed for Text3d ({:?}); rebuilding meshes",
+                entity
+            );
+            text_mesh.mark_meshes_stale();
         }
     }
 }
@@ -913,17 +1830,776 @@ impl Default for TextMeshPlugin {
 impl Plugin for TextMeshPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.config.clone())
+            .init_resource::<GlyphProfileMeshCache>()
+            .init_resource::<GlyphProfileMaterials>()
+            .init_resource::<GlyphInstanceMeshes>()
+            .init_resource::<Text3dBatches>()
+            .init_resource::<GlyphMaterialCache>()
+            .init_resource::<LitGlyphMaterialCache>()
+            .init_resource::<GlyphRecordWrites>()
+            .init_resource::<GlyphLabels>()
+            .add_systems(First, clear_glyph_record_writes_system)
             .add_systems(Update, update_font_atlases_system)
+            .add_systems(
+                Update,
+                select_sdf_tier_system.before(update_font_atlases_system),
+            )
             .add_systems(
                 PostUpdate,
-                create_shadow_caster_meshes_system.after(update_font_atlases_system),
+                create_shadow_caster_meshes_system
+                    .after(update_font_atlases_system)
+                    .after(apply_text_mesh_config_changes_system),
             )
+            .add_systems(PostUpdate, apply_text_mesh_config_changes_system)
             .add_systems(
                 PostUpdate,
                 update_atlas_meshes_system.after(create_shadow_caster_meshes_system),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_text3d_style_system
+                    .after(create_shadow_caster_meshes_system)
+                    .before(update_atlas_meshes_system),
+            )
+            .add_systems(
+                PostUpdate,
+                upload_glyph_labels_system.after(apply_text3d_style_system),
+            )
+            .add_systems(PostUpdate, warn_xray_without_depth_prepass_system)
+            .add_systems(
+                PostUpdate,
+                apply_text3d_lighting_system
+                    .after(apply_text3d_style_system)
+                    .before(update_atlas_meshes_system),
+            )
+            .add_systems(
+                PostUpdate,
+                update_text3d_lod_system
+                    .after(TransformSystems::Propagate)
+                    .before(create_shadow_caster_meshes_system),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_text3d_lod_system.after(create_shadow_caster_meshes_system),
+            )
+            .add_systems(
+                PostUpdate,
+                update_text_batches_system
+                    .after(create_shadow_caster_meshes_system)
+                    .after(TransformSystems::Propagate)
+                    .after(VisibilitySystems::VisibilityPropagate),
             );
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<GlyphRecordWrites>()
+                .add_systems(ExtractSchedule, extract_glyph_record_writes_system)
+                .add_systems(
+                    Render,
+                    write_glyph_records_system.in_set(RenderSystems::PrepareResources),
+                );
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        let labels = app.world().resource::<GlyphLabels>().buffer();
+        if let Some(mut buffers) = app
+            .world_mut()
+            .get_resource_mut::<Assets<ShaderStorageBuffer>>()
+        {
+            // Bound by every non-instanced `GlyphMaterial`. Storage buffers can't be empty,
+            // so it holds a single zeroed record.
+            buffers
+                .insert(
+                    &EMPTY_GLYPH_INSTANCES,
+                    ShaderStorageBuffer::from(vec![GlyphInstance::default()]),
+                )
+                .expect("UUID asset ids are always valid");
+            buffers
+                .insert(&GLYPH_LABELS, labels)
+                .expect("UUID asset ids are always valid");
+        }
     }
 }
 
 // Note: OnlyShadowCaster is provided by Bevy's light module (patched Bevy), and is used as
 // a marker component to ensure hidden entities still contribute to shadow passes.
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::font::GlyphId;
+    use crate::lit::{LitGlyphMaterial, Text3dLit};
+    use crate::pipeline_material::{
+        GlyphDepthKey, GlyphLabel, GlyphMaterialKey, specialize_glyph_depth,
+        specialize_glyph_material, specialize_glyph_vertices,
+    };
+    use crate::style::{GlyphAlphaMode, Text3dBillboard, Text3dDepthMode, Text3dScreenSize};
+    use bevy::pbr::{Material, OpaqueRendererMethod};
+    use bevy::prelude::Mut;
+    use bevy::render::render_resource::{
+        CompareFunction, DepthStencilState, FragmentState, RenderPipelineDescriptor, ShaderSize,
+        TextureFormat,
+    };
+    use bevy_mesh::MeshTag;
+
+    fn glyphs_for(text: &str) -> Box<[Glyph]> {
+        text.chars()
+            .map(|c| Glyph::from_cursor(Rect::default(), c, [1.0; 4]))
+            .collect()
+    }
+
+    /// Builds an app running the text mesh systems with the bundled font, without rendering.
+    pub(crate) fn test_app() -> (App, Handle<Font>) {
+        let mut app = App::new();
+        app.add_plugins(TextMeshPlugin::default())
+            .init_resource::<FontAtlasSets>()
+            .init_resource::<Assets<Font>>()
+            .init_resource::<Assets<Image>>()
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<GlyphMaterial>>()
+            .init_resource::<Assets<LitGlyphMaterial>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<Assets<crate::pipeline_material::DepthOnlyMaterial>>()
+            .init_resource::<Assets<ShaderStorageBuffer>>();
+        let face = owned_ttf_parser::OwnedFace::from_vec(
+            include_bytes!("../assets/fonts/FiraCode-Bold.ttf").to_vec(),
+            0,
+        )
+        .expect("bundled font parses");
+        let font = app
+            .world_mut()
+            .resource_mut::<Assets<Font>>()
+            .add(Font::from(face));
+        (app, font)
+    }
+
+    /// Spawns a `Text3d` showing `text` in `font`.
+    pub(crate) fn spawn_text(app: &mut App, font: &Handle<Font>, text: &str) -> Entity {
+        let mut text_mesh = Text3d::new(font.clone());
+        text_mesh.set_glyphs(glyphs_for(text));
+        app.world_mut().spawn(text_mesh).id()
+    }
+
+    /// Sets the glyphs of the `Text3d` on `entity` to `text`.
+    pub(crate) fn set_text(app: &mut App, entity: Entity, text: &str) {
+        app.world_mut()
+            .get_mut::<Text3d>(entity)
+            .unwrap()
+            .set_glyphs(glyphs_for(text));
+    }
+
+    fn text3d(app: &App, entity: Entity) -> &Text3d {
+        app.world().get::<Text3d>(entity).unwrap()
+    }
+
+    /// Fills the first atlas page of `font` until a code point lands on the second one.
+    fn code_point_on_second_page(app: &mut App, font: &Handle<Font>) -> char {
+        let world = app.world_mut();
+        world.resource_scope(|world, mut atlases: Mut<FontAtlasSets>| {
+            world.resource_scope(|world, mut textures: Mut<Assets<Image>>| {
+                let fonts = world.resource::<Assets<Font>>();
+                ('!'..=char::MAX)
+                    .find(|&code_point| {
+                        atlases.add_code_points(&[code_point], font.id(), fonts, &mut textures);
+                        atlases.get(font.id()).unwrap().atlas(code_point) == Some(1)
+                    })
+                    .expect("font fills more than one atlas page")
+            })
+        })
+    }
+
+    #[test]
+    fn test_set_glyphs_queues_unique_code_points() {
+        let mut text = Text3d::new(Handle::default());
+        text.add_missing(&['0', '1']);
+        text.set_glyphs(glyphs_for("1001"));
+        assert_eq!(text.missing, vec!['0', '1']);
+
+        text.set_glyphs(glyphs_for("ab"));
+        assert_eq!(text.missing, vec!['0', '1', 'a', 'b']);
+    }
+
+    #[test]
+    fn test_quad_rect_honors_placement() {
+        let info = GlyphInfo {
+            id: GlyphId(1),
+            advance: Vec2::new(0.6, 0.0),
+            offset: Vec2::new(0.1, -0.2),
+            size: Vec2::new(0.5, 0.7),
+        };
+        let position = Rect::new(1.0, 2.0, 3.0, 5.0);
+
+        let cursor = Glyph::from_cursor(position, 'A', [1.0; 4]);
+        let quad = cursor.quad_rect(&info, Vec2::splat(2.0));
+        assert!((quad.min - Vec2::new(1.1, 1.8)).length() < 1e-6);
+        assert!((quad.max - Vec2::new(2.1, 3.2)).length() < 1e-6);
+
+        let rect = Glyph::from_rect(position, 'A', [1.0; 4]);
+        let quad = rect.quad_rect(&info, Vec2::splat(2.0));
+        assert_eq!(quad, position);
+        // The far corner of the glyph's profile lands on the far corner of the rect.
+        let corner = Glyph::profile_point(&quad, &info, info.size);
+        assert!((corner - position.max).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_glyph_transform_pivots_around_quad_center() {
+        let quad = Rect::new(0.0, 0.0, 2.0, 2.0);
+        let glyph = Glyph::from_rect(quad, 'A', [1.0; 4]).with_transform(GlyphTransform {
+            rotation: std::f32::consts::FRAC_PI_2,
+            scale: Vec2::new(2.0, 1.0),
+            z_offset: 0.5,
+            ..Default::default()
+        });
+        // (2, 1) is 1 unit right of the center; scaled to 2 units, then rotated to point up.
+        let point = glyph.transform_point(&quad, Vec2::new(2.0, 1.0));
+        assert!((point - Vec3::new(1.0, 3.0, 0.5)).length() < 1e-5);
+
+        let slanted = GlyphTransform {
+            shear: Vec2::new(0.5, 0.0),
+            ..Default::default()
+        };
+        let point = slanted.apply(Vec2::ZERO, Vec2::new(0.0, 2.0));
+        assert!((point - Vec3::new(1.0, 2.0, 0.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_removing_text3d_despawns_children() {
+        let mut world = bevy::ecs::world::World::new();
+        let atlas_child = world.spawn_empty().id();
+        let profile_child = world.spawn_empty().id();
+
+        let mut text = Text3d::new(Handle::default());
+        text.child_entities.insert(0, atlas_child);
+        text.glyph_profile_child_entity = Some(profile_child);
+        let entity = world.spawn(text).id();
+
+        world.entity_mut(entity).remove::<Text3d>();
+        world.flush();
+
+        assert!(world.get_entity(atlas_child).is_err());
+        assert!(world.get_entity(profile_child).is_err());
+        assert!(world.get_entity(entity).is_ok());
+    }
+
+    #[test]
+    fn test_atlas_children_follow_atlas_pages() {
+        let (mut app, font) = test_app();
+        let second = code_point_on_second_page(&mut app, &font);
+        let entity = spawn_text(&mut app, &font, &format!("A{second}"));
+        app.update();
+        let children: HashMap<usize, Entity> = text3d(&app, entity)
+            .atlas_children()
+            .map(|(atlas, child, _)| (atlas, child))
+            .collect();
+        assert_eq!(children.len(), 2);
+
+        set_text(&mut app, entity, &second.to_string());
+        app.update();
+        let remaining: Vec<_> = text3d(&app, entity).atlas_children().collect();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!((remaining[0].0, remaining[0].1), (1, children[&1]));
+        assert!(app.world().get_entity(children[&0]).is_err());
+
+        set_text(&mut app, entity, "AB");
+        app.update();
+        let remaining: Vec<_> = text3d(&app, entity).atlas_children().collect();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].0, 0);
+        assert!(app.world().get_entity(children[&1]).is_err());
+    }
+
+    #[test]
+    fn test_profile_child_is_reused_on_text_change() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.update();
+        let child = text3d(&app, entity).glyph_profile_child_entity.unwrap();
+        let mesh = text3d(&app, entity).glyph_profile_mesh_handle().unwrap();
+
+        set_text(&mut app, entity, "CDE");
+        app.update();
+        let text = text3d(&app, entity);
+        assert_eq!(text.glyph_profile_child_entity, Some(child));
+        assert_eq!(text.glyph_profile_mesh_handle(), Some(mesh));
+        assert!(app.world().get_entity(child).is_ok());
+    }
+
+    #[test]
+    fn test_profile_children_share_a_material() {
+        let (mut app, font) = test_app();
+        let first = spawn_text(&mut app, &font, "AB");
+        let second = spawn_text(&mut app, &font, "CD");
+        app.update();
+        let material = |entity| {
+            let child = text3d(&app, entity).glyph_profile_child_entity.unwrap();
+            app.world()
+                .get::<MeshMaterial3d<crate::pipeline_material::DepthOnlyMaterial>>(child)
+                .unwrap()
+                .id()
+        };
+        assert_eq!(material(first), material(second));
+        let materials = app
+            .world()
+            .resource::<Assets<crate::pipeline_material::DepthOnlyMaterial>>();
+        assert_eq!(materials.len(), 1);
+    }
+
+    #[test]
+    fn test_sdf_masked_text_casts_shadows_from_its_quads() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.update();
+        let (_, child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        let profile = text3d(&app, entity).glyph_profile_child_entity.unwrap();
+        assert!(app.world().entity(child).contains::<NotShadowCaster>());
+
+        let set_mode = |app: &mut App, mode| {
+            app.world_mut()
+                .get_mut::<Text3d>(entity)
+                .unwrap()
+                .set_glyph_profile_mode(mode);
+            app.update();
+        };
+        set_mode(&mut app, GlyphProfileRenderMode::SdfMasked);
+        assert!(!app.world().entity(child).contains::<NotShadowCaster>());
+        assert_eq!(text3d(&app, entity).glyph_profile_child_entity, None);
+        assert!(app.world().get_entity(profile).is_err());
+
+        set_mode(&mut app, GlyphProfileRenderMode::DepthOnly);
+        assert!(app.world().entity(child).contains::<NotShadowCaster>());
+        assert!(text3d(&app, entity).glyph_profile_child_entity.is_some());
+    }
+
+    #[test]
+    fn test_billboarded_text_has_no_profile_child() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(Text3dStyle::default().with_billboard(Text3dBillboard::Spherical));
+        app.update();
+        assert_eq!(text3d(&app, entity).glyph_profile_child_entity, None);
+        let (_, child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        assert!(app.world().entity(child).contains::<NotShadowCaster>());
+
+        // Its masked quads turn towards the light in shadow maps instead.
+        app.world_mut()
+            .get_mut::<Text3d>(entity)
+            .unwrap()
+            .set_glyph_profile_mode(GlyphProfileRenderMode::SdfMasked);
+        app.update();
+        assert!(!app.world().entity(child).contains::<NotShadowCaster>());
+        assert_eq!(text3d(&app, entity).glyph_profile_child_entity, None);
+    }
+
+    #[test]
+    fn test_fading_and_tinting_keep_the_material() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.update();
+        let (_, child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        let material = app
+            .world()
+            .get::<MeshMaterial3d<GlyphMaterial>>(child)
+            .unwrap()
+            .0
+            .clone();
+
+        let tag = app.world().get::<MeshTag>(child).unwrap().0;
+        assert_ne!(tag, 0);
+
+        // Tints brighter than white aren't clamped, and only the label's record is written.
+        let style = Text3dStyle {
+            opacity: 0.25,
+            tint: Color::linear_rgb(2.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        app.world_mut().entity_mut(entity).insert(style.clone());
+        app.update();
+        let child_ref = app.world().entity(child);
+        assert_eq!(
+            child_ref.get::<MeshMaterial3d<GlyphMaterial>>().unwrap().0,
+            material
+        );
+        assert_eq!(child_ref.get::<MeshTag>().unwrap().0, tag);
+        assert!(text3d(&app, entity).quads_up_to_date());
+        assert_eq!(app.world().resource::<Assets<GlyphMaterial>>().len(), 1);
+        let label = *app.world().resource::<GlyphLabels>().get(entity).unwrap();
+        assert_eq!(label.tint, Vec4::new(2.0, 0.0, 0.0, 1.0));
+        assert_eq!(label.opacity, 0.25);
+        let writes = app.world().resource::<GlyphRecordWrites>().writes();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].0, GLYPH_LABELS.id());
+        assert_eq!(writes[0].1, u64::from(tag) * GlyphLabel::SHADER_SIZE.get());
+
+        // Removing the text frees its slot for the next one.
+        app.world_mut().entity_mut(entity).despawn();
+        let next = spawn_text(&mut app, &font, "AB");
+        app.update();
+        let (_, child, _) = text3d(&app, next).atlas_children().next().unwrap();
+        assert_eq!(app.world().get::<MeshTag>(child).unwrap().0, tag);
+        let label = app.world().resource::<GlyphLabels>().get(next).unwrap();
+        assert_eq!(label.tint, Vec4::ONE);
+    }
+
+    #[test]
+    fn test_lighting_follows_changes_and_new_children() {
+        let (mut app, font) = test_app();
+        let second = code_point_on_second_page(&mut app, &font);
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(Text3dLit::default());
+        app.update();
+        let (_, child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        let lit_ticks = |app: &App| {
+            app.world()
+                .entity(child)
+                .get_ref::<MeshMaterial3d<LitGlyphMaterial>>()
+                .unwrap()
+                .last_changed()
+        };
+        assert!(
+            !app.world()
+                .entity(child)
+                .contains::<MeshMaterial3d<GlyphMaterial>>()
+        );
+        let lit = lit_ticks(&app);
+
+        // Idle and faded text keeps its lit material untouched.
+        app.update();
+        app.world_mut().entity_mut(entity).insert(Text3dStyle {
+            opacity: 0.5,
+            ..Default::default()
+        });
+        app.update();
+        assert_eq!(lit_ticks(&app), lit);
+
+        // Children created for new atlas pages are lit too.
+        set_text(&mut app, entity, &format!("A{second}"));
+        app.update();
+        for (_, child, _) in text3d(&app, entity).atlas_children() {
+            let child = app.world().entity(child);
+            assert!(child.contains::<MeshMaterial3d<LitGlyphMaterial>>());
+            assert!(!child.contains::<MeshMaterial3d<GlyphMaterial>>());
+        }
+
+        app.world_mut().entity_mut(entity).remove::<Text3dLit>();
+        app.update();
+        for (_, child, _) in text3d(&app, entity).atlas_children() {
+            let child = app.world().entity(child);
+            assert!(child.contains::<MeshMaterial3d<GlyphMaterial>>());
+            assert!(!child.contains::<MeshMaterial3d<LitGlyphMaterial>>());
+        }
+    }
+
+    #[test]
+    fn test_screen_sized_text_draws_from_its_sdf_tier() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.world_mut()
+            .entity_mut(entity)
+            .insert(Text3dStyle::default().with_screen_size(Text3dScreenSize::new(24.0)));
+        app.update();
+        assert_eq!(text3d(&app, entity).sdf_tier(), SdfTier::Small);
+        let atlas_texture = |app: &App, child: Entity| {
+            let material = app
+                .world()
+                .get::<MeshMaterial3d<GlyphMaterial>>(child)
+                .unwrap();
+            let materials = app.world().resource::<Assets<GlyphMaterial>>();
+            materials.get(&material.0).unwrap().sdf_texture.clone()
+        };
+        let (atlas, small_child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        let atlases = app.world().resource::<FontAtlasSets>();
+        let small = atlases.get_tier(font.id(), SdfTier::Small).unwrap();
+        assert!(small.has_glyph('A'));
+        assert!(atlases.get(font.id()).is_none());
+        assert_eq!(
+            atlas_texture(&app, small_child),
+            small.atlas_texture(atlas).unwrap()
+        );
+
+        // Back in world space, the text is redrawn from the regular atlases.
+        app.world_mut().entity_mut(entity).remove::<Text3dStyle>();
+        app.update();
+        assert_eq!(text3d(&app, entity).sdf_tier(), SdfTier::Regular);
+        assert!(app.world().get_entity(small_child).is_err());
+        let (atlas, child, _) = text3d(&app, entity).atlas_children().next().unwrap();
+        let regular = app
+            .world()
+            .resource::<FontAtlasSets>()
+            .get(font.id())
+            .unwrap();
+        assert_eq!(
+            atlas_texture(&app, child),
+            regular.atlas_texture(atlas).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_config_changes_mark_meshes_stale() {
+        let (mut app, font) = test_app();
+        let first = spawn_text(&mut app, &font, "AB");
+        let second = spawn_text(&mut app, &font, "CD");
+        app.update();
+        let generation = |app: &App, entity| text3d(app, entity).generation;
+        let (first_generation, second_generation) =
+            (generation(&app, first), generation(&app, second));
+
+        app.world_mut()
+            .entity_mut(second)
+            .insert(TextMeshConfigOverride {
+                font_scale: Some(Vec3::splat(2.0)),
+                ..Default::default()
+            });
+        app.update();
+        assert_eq!(generation(&app, first), first_generation);
+        assert!(generation(&app, second) > second_generation);
+        assert!(text3d(&app, second).quads_up_to_date());
+
+        let second_generation = generation(&app, second);
+        app.world_mut()
+            .resource_mut::<TextMeshPluginConfig>()
+            .text_mesh_shadow_quality = GlyphTessellationQuality::Low;
+        app.update();
+        assert!(generation(&app, first) > first_generation);
+        assert!(generation(&app, second) > second_generation);
+        assert!(text3d(&app, first).quads_up_to_date());
+    }
+
+    #[test]
+    fn test_idle_text_skips_quad_rebuild() {
+        let (mut app, font) = test_app();
+        let entity = spawn_text(&mut app, &font, "AB");
+        app.update();
+        app.update();
+
+        // Stale quads are only noticed once the text is flagged as changed.
+        app.world_mut()
+            .get_mut::<Text3d>(entity)
+            .unwrap()
+            .bypass_change_detection()
+            .invalidate_quads();
+        app.update();
+        assert!(!text3d(&app, entity).quads_up_to_date());
+
+        app.world_mut()
+            .get_mut::<Text3d>(entity)
+            .unwrap()
+            .set_changed();
+        app.update();
+        assert!(text3d(&app, entity).quads_up_to_date());
+    }
+
+    #[test]
+    fn test_builder_packs_vertices() {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD,
+        );
+        let mut builder = Text3dBuilder::new(&mut mesh, false, false);
+        builder.append_glyph(
+            &Rect::new(0.0, 0.0, 1.0, 2.0),
+            None,
+            &Rect::new(0.0, 0.5, 0.25, 1.0),
+            &[1.0, 0.5, 0.0, 1.0],
+            0.0,
+        );
+
+        assert!(!mesh.contains_attribute(Mesh::ATTRIBUTE_POSITION));
+        let Some(VertexAttributeValues::Unorm16x2(uvs)) = mesh.attribute(ATTRIBUTE_UV) else {
+            panic!("expected Unorm16x2 UVs");
+        };
+        assert_eq!(uvs[2], [u16::MAX / 4 + 1, u16::MAX]);
+        let Some(VertexAttributeValues::Unorm8x4(colors)) = mesh.attribute(ATTRIBUTE_COLOR) else {
+            panic!("expected Unorm8x4 colors");
+        };
+        assert_eq!(colors[0], [255, 128, 0, 255]);
+        let Some(VertexAttributeValues::Unorm16x2(coords)) = mesh.attribute(ATTRIBUTE_GLYPH_COORD)
+        else {
+            panic!("expected Unorm16x2 glyph coordinates");
+        };
+        assert_eq!(coords[1], [u16::MAX, 0]);
+
+        assert!(!mesh.contains_attribute(ATTRIBUTE_GLYPH_EMISSIVE));
+
+        let mut builder = Text3dBuilder::new(&mut mesh, true, true);
+        builder.append_glyph(
+            &Rect::new(0.0, 0.0, 1.0, 2.0),
+            None,
+            &Rect::new(0.0, 0.5, 0.25, 1.0),
+            &[1.0, 0.5, 0.0, 1.0],
+            4.0,
+        );
+        assert!(mesh.contains_attribute(Mesh::ATTRIBUTE_POSITION));
+        let Some(VertexAttributeValues::Float32(emissives)) =
+            mesh.attribute(ATTRIBUTE_GLYPH_EMISSIVE)
+        else {
+            panic!("expected Float32 emissive intensities");
+        };
+        assert_eq!(emissives, &vec![4.0; 4]);
+    }
+
+    #[test]
+    fn test_emissive_attribute_follows_pipeline_key() {
+        let mut layouts = bevy_mesh::MeshVertexBufferLayouts::default();
+        for emissive in [false, true] {
+            let mut mesh = Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::MAIN_WORLD,
+            );
+            Text3dBuilder::new(&mut mesh, false, emissive).append_glyph(
+                &Rect::new(0.0, 0.0, 1.0, 1.0),
+                None,
+                &Rect::new(0.0, 0.0, 1.0, 1.0),
+                &[1.0; 4],
+                if emissive { 2.0 } else { 0.0 },
+            );
+            let layout = mesh.get_mesh_vertex_buffer_layout(&mut layouts);
+            let mut descriptor = RenderPipelineDescriptor::default();
+            specialize_glyph_vertices(&mut descriptor, &layout).unwrap();
+            assert_eq!(
+                descriptor
+                    .vertex
+                    .shader_defs
+                    .contains(&"GLYPH_EMISSIVE".into()),
+                emissive
+            );
+            let locations: Vec<_> = descriptor.vertex.buffers[0]
+                .attributes
+                .iter()
+                .map(|attribute| attribute.shader_location)
+                .collect();
+            assert_eq!(locations.contains(&4), emissive);
+        }
+    }
+
+    #[test]
+    fn test_deferred_mask_material_specializes() {
+        let style = Text3dStyle {
+            alpha_mode: GlyphAlphaMode::Mask(0.5),
+            ..Default::default()
+        }
+        .with_opaque_render_method(OpaqueRendererMethod::Deferred);
+        let material = style.material(Handle::default(), 6);
+        assert_eq!(
+            Material::opaque_render_method(&material),
+            OpaqueRendererMethod::Deferred
+        );
+        assert_eq!(Material::alpha_mode(&material), AlphaMode::Mask(0.5));
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::MAIN_WORLD,
+        );
+        Text3dBuilder::new(&mut mesh, false, false).append_glyph(
+            &Rect::new(0.0, 0.0, 1.0, 1.0),
+            None,
+            &Rect::new(0.0, 0.0, 1.0, 1.0),
+            &[1.0; 4],
+            0.0,
+        );
+        let layout = mesh.get_mesh_vertex_buffer_layout(&mut Default::default());
+        // The gbuffer pass runs the prepass shader with the deferred outputs.
+        let mut descriptor = RenderPipelineDescriptor {
+            depth_stencil: Some(DepthStencilState {
+                format: TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            fragment: Some(FragmentState::default()),
+            ..Default::default()
+        };
+        descriptor
+            .vertex
+            .shader_defs
+            .extend(["PREPASS_PIPELINE".into(), "DEFERRED_PREPASS".into()]);
+        specialize_glyph_material(&mut descriptor, &layout, GlyphMaterialKey::from(&material))
+            .unwrap();
+        assert_eq!(descriptor.vertex.buffers.len(), 1);
+        assert_eq!(
+            descriptor.fragment.unwrap().entry_point,
+            Some("fragment".into())
+        );
+        let depth_stencil = descriptor.depth_stencil.unwrap();
+        assert_eq!(depth_stencil.depth_compare, CompareFunction::GreaterEqual);
+        assert!(depth_stencil.depth_write_enabled);
+    }
+
+    #[test]
+    fn test_decal_bias_applies_to_main_pass_only() {
+        let key = GlyphDepthKey::from(Text3dDepthMode::SurfaceDecal { depth_bias: 50.0 });
+        let descriptor = |prepass: bool| {
+            let mut descriptor = RenderPipelineDescriptor {
+                depth_stencil: Some(DepthStencilState {
+                    format: TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: CompareFunction::GreaterEqual,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+                ..Default::default()
+            };
+            if prepass {
+                descriptor
+                    .vertex
+                    .shader_defs
+                    .push("PREPASS_PIPELINE".into());
+            }
+            specialize_glyph_depth(&mut descriptor, key);
+            descriptor.depth_stencil.unwrap().bias.constant
+        };
+        assert_eq!(descriptor(false), 50);
+        assert_eq!(descriptor(true), 0);
+    }
+
+    #[test]
+    fn test_instance_meshes_share_capacity() {
+        let mut meshes = Assets::<Mesh>::default();
+        let mut instance_meshes = GlyphInstanceMeshes::default();
+
+        let short = instance_meshes.mesh(3, &mut meshes);
+        assert_eq!(short, instance_meshes.mesh(0, &mut meshes));
+        assert_eq!(short, instance_meshes.mesh(16, &mut meshes));
+
+        let long = instance_meshes.mesh(17, &mut meshes);
+        assert_ne!(short, long);
+        let vertex_count = meshes.get(&long).unwrap().count_vertices();
+        assert_eq!(vertex_count, 32 * 6);
+    }
+
+    #[test]
+    fn test_pad_quad_grows_by_texel_size() {
+        // Quads grow by the padding at the glyph's texel size.
+        let quad = Rect::new(0.0, 0.0, 2.0, 4.0);
+        let uv = Rect::new(0.5, 0.5, 0.6, 0.7);
+        let padded = pad_quad(&quad, &uv, &Rect::new(0.45, 0.45, 0.65, 0.75));
+        assert!((padded.min - Vec2::new(-1.0, -1.0)).length() < 1e-5);
+        assert!((padded.max - Vec2::new(3.0, 5.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_billboard_bounds_fit_any_rotation() {
+        let corners = quad_corners(&Rect::new(0.5, -0.25, 3.0, 1.0), None);
+        let aabb = billboard_bounds(Aabb::enclosing(corners).unwrap());
+        let (min, max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+        for rotation in [
+            bevy::math::Quat::from_rotation_y(2.0),
+            bevy::math::Quat::from_rotation_x(-1.0) * bevy::math::Quat::from_rotation_z(0.7),
+        ] {
+            for corner in corners {
+                let turned = rotation * corner;
+                assert!(turned.cmpge(min - 1e-5).all() && turned.cmple(max + 1e-5).all());
+            }
+        }
+
+        let style = Text3dStyle::default().with_billboard(Text3dBillboard::Cylindrical);
+        assert_eq!(style.uniform(6).billboard, 2);
+        assert_ne!(style.key(), Text3dStyle::default().key());
+    }
+}
@@ -1,34 +1,210 @@
 // Borrowed from: https://bevyengine.org/examples/shader_advanced/custom_vertex_attribute.rs
 use bevy::{
-    asset::Asset,
-    math::Vec4,
-    prelude::{AlphaMode, Handle, Image, Material, Mesh},
+    asset::{Asset, AssetId, uuid_handle},
+    math::{Mat4, Vec4},
+    pbr::OpaqueRendererMethod,
+    prelude::{AlphaMode, Assets, Handle, Image, Material, Mesh, Res, ResMut, Resource},
     reflect::TypePath,
-    render::render_resource::{
-        AsBindGroup, ColorWrites, CompareFunction, ShaderType, VertexFormat,
+    render::{
+        Extract,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, BufferUsages, ColorWrites, CompareFunction, ShaderSize, ShaderType,
+            VertexFormat, encase::StorageBuffer, encase::internal::WriteInto,
+        },
+        renderer::RenderQueue,
+        storage::{GpuShaderStorageBuffer, ShaderStorageBuffer},
     },
     shader::ShaderRef,
 };
 use bevy_mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
 
+use crate::style::Text3dDepthMode;
+
+/// Glyph vertex position in text space. Z carries per-glyph depth offsets.
 pub const ATTRIBUTE_POSITION: MeshVertexAttribute =
-    MeshVertexAttribute::new("Glyph_Vertex_Position", 988540917, VertexFormat::Float32x2);
+    MeshVertexAttribute::new("Glyph_Vertex_Position", 988540917, VertexFormat::Float32x3);
+
+/// Glyph atlas UV, normalized to 16 bits per component.
+pub const ATTRIBUTE_UV: MeshVertexAttribute =
+    MeshVertexAttribute::new("Glyph_Vertex_Uv", 988540919, VertexFormat::Unorm16x2);
+
+/// Glyph color, normalized to 8 bits per component.
+pub const ATTRIBUTE_COLOR: MeshVertexAttribute =
+    MeshVertexAttribute::new("Glyph_Vertex_Color", 988540920, VertexFormat::Unorm8x4);
+
+/// Position of the vertex within its glyph quad, from `(0, 0)` at the quad's min corner to
+/// `(1, 1)` at its max corner. Used by fills in [`FillSpace::Glyph`](crate::FillSpace::Glyph).
+pub const ATTRIBUTE_GLYPH_COORD: MeshVertexAttribute =
+    MeshVertexAttribute::new("Glyph_Vertex_Coord", 988540921, VertexFormat::Unorm16x2);
+
+/// Light emitted by the glyph as a multiple of its color; see `Glyph::emissive`. Only
+/// written into meshes with emissive glyphs, which `text3d.wgsl` reads with `GLYPH_EMISSIVE`.
+pub const ATTRIBUTE_GLYPH_EMISSIVE: MeshVertexAttribute =
+    MeshVertexAttribute::new("Glyph_Vertex_Emissive", 988540922, VertexFormat::Float32);
+
+/// Index of a vertex within an instanced glyph mesh. `glyph_vertex` in `text3d_glyph.wgsl`
+/// derives the glyph record and quad corner from it; `@builtin(vertex_index)` can't be used
+/// because the mesh allocator offsets it by the mesh's position in its slab.
+pub const ATTRIBUTE_GLYPH_VERTEX_INDEX: MeshVertexAttribute =
+    MeshVertexAttribute::new("Glyph_Vertex_Index", 988540918, VertexFormat::Uint32);
+
+/// Placeholder storage buffer bound in place of the glyph instances of materials that read
+/// glyphs from vertex attributes.
+pub const EMPTY_GLYPH_INSTANCES: Handle<ShaderStorageBuffer> =
+    uuid_handle!("5f0c7d52-3b8e-4a51-9e0f-2d6a4c1b7e93");
+
+/// [`GlyphLabel`] records of every unbatched `Text3d`, bound by all glyph materials that
+/// aren't batched. Each atlas child finds its label at the index in its `MeshTag`; see
+/// [`GlyphLabels`](crate::style::GlyphLabels).
+pub const GLYPH_LABELS: Handle<ShaderStorageBuffer> =
+    uuid_handle!("b3d1e8a4-7c62-4f09-8a5e-1f4c9d2b6e70");
+
+/// A single glyph of an instanced `Text3d`, expanded to a quad by `glyph_vertex` in
+/// `text3d_glyph.wgsl`.
+///
+/// Must match the `GlyphInstance` struct in `text3d_glyph.wgsl`.
+#[derive(Clone, Copy, Debug, Default, ShaderType)]
+pub struct GlyphInstance {
+    /// Quad rectangle in text space as `(min.x, min.y, max.x, max.y)`.
+    pub rect: Vec4,
+    /// Atlas UV rectangle as `(min.x, min.y, max.x, max.y)`.
+    pub uv: Vec4,
+    pub color: Vec4,
+    /// Columns of the 2x2 glyph transform, applied around the quad center.
+    pub linear: Vec4,
+    pub z_offset: f32,
+    /// Atlas page holding the glyph.
+    pub atlas: u32,
+    /// Index into the label transforms of a batched material; unused otherwise.
+    pub label: u32,
+    /// Light emitted as a multiple of `color`.
+    pub emissive: f32,
+}
+
+/// A label drawn by a [`GlyphMaterial`]: one of the labels merged into a batch, indexed by
+/// [`GlyphInstance::label`], or an unbatched `Text3d` in [`GLYPH_LABELS`].
+///
+/// Must match the `GlyphLabel` struct in `text3d_glyph.wgsl`.
+#[derive(Clone, Copy, Debug, PartialEq, ShaderType)]
+pub struct GlyphLabel {
+    /// World transform of a batched label; the batch entity sits at the origin. Unbatched
+    /// labels keep the identity, as their meshes are placed by their own transforms.
+    pub world_from_label: Mat4,
+    /// World transform of the label in the previous frame, for motion vectors.
+    pub previous_world_from_label: Mat4,
+    /// Linear RGBA multiplied with the fill of the label's glyphs.
+    pub tint: Vec4,
+    /// Multiplied with the alpha of the label's glyphs.
+    pub opacity: f32,
+}
 
+impl Default for GlyphLabel {
+    fn default() -> Self {
+        Self {
+            world_from_label: Mat4::IDENTITY,
+            previous_world_from_label: Mat4::IDENTITY,
+            tint: Vec4::ONE,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Maximum number of outlines a [`GlyphMaterial`] draws around its glyphs.
+///
+/// Must match `MAX_GLYPH_OUTLINES` in `text3d_glyph.wgsl`.
+pub const MAX_GLYPH_OUTLINES: usize = 4;
+
+/// Uniforms of a [`GlyphMaterial`]; see `Text3dStyle` for what each parameter does.
+///
+/// SDF offsets are normalized, so `0.5` reaches as far from the glyph edge as the atlas
+/// `range` allows. Must match `GlyphMaterialUniform` in `text3d_glyph.wgsl`.
 #[derive(Clone, Copy, Debug, ShaderType)]
 pub struct GlyphMaterialUniform {
+    /// x: smoothing, y: weight offset, z: opacity, w: outline count. Styles leave the
+    /// opacity at 1.0 and give each label its own in its [`GlyphLabel`].
     pub params: Vec4,
+    /// Linear RGBA of each outline, innermost first.
+    pub outline_colors: [Vec4; MAX_GLYPH_OUTLINES],
+    /// SDF offset of the outer edge of each outline, innermost first.
+    pub outline_edges: Vec4,
+    /// Linear RGBA of the glow; fully transparent disables it.
+    pub glow_color: Vec4,
+    /// x: SDF distance over which the glow fades out.
+    pub glow: Vec4,
+    /// Linear RGBA of the drop shadow; fully transparent disables it.
+    pub shadow_color: Vec4,
+    /// xy: shadow offset in atlas texels, z: SDF distance over which its edge fades out.
+    pub shadow: Vec4,
+    /// x: fill mode (0 vertex color, 1 linear gradient, 2 radial gradient, 3 image),
+    /// y: fill space (0 text, 1 glyph).
+    pub fill: Vec4,
+    /// Linear RGBA at the start of a gradient fill.
+    pub fill_start_color: Vec4,
+    /// Linear RGBA at the end of a gradient fill.
+    pub fill_end_color: Vec4,
+    /// Linear gradient: start and end points. Radial gradient: center and radius.
+    /// Image: scale and offset of the image UVs.
+    pub fill_geometry: Vec4,
+    /// x: SDF distance from the glyph edge to the bevel top, zero without a bevel,
+    /// y: bevel height over its width.
+    pub bevel: Vec4,
+    /// xyz: normalized text space direction towards the light shading unlit bevels.
+    pub bevel_light: Vec4,
+    /// x: emission as a multiple of the glyph color, y: pulse frequency in Hz,
+    /// z: pulse depth, w: flicker depth.
+    pub emissive: Vec4,
+    /// x: on-screen pixels per text space unit, zero without screen sizing,
+    /// y: minimum and z: maximum world size of a text space unit, zero when unbounded.
+    pub screen_size: Vec4,
+    /// Alpha below which masked glyphs are discarded; see [`GlyphMaterial::alpha_mode`].
+    pub alpha_cutoff: f32,
+    /// Which way the glyphs turn to face each view: 0 not at all, 1 spherical,
+    /// 2 cylindrical, 3 screen aligned.
+    pub billboard: u32,
+    /// Opacity of x-ray glyphs where other surfaces hide them; see [`Text3dDepthMode::XRay`].
+    pub occluded_opacity: f32,
 }
 
 impl GlyphMaterialUniform {
     pub fn with_smoothing(smoothing: f32) -> Self {
         Self {
-            params: Vec4::new(smoothing, 0.0, 0.0, 0.0),
+            params: Vec4::new(smoothing, 0.0, 1.0, 0.0),
+            outline_colors: [Vec4::ZERO; MAX_GLYPH_OUTLINES],
+            outline_edges: Vec4::ZERO,
+            glow_color: Vec4::ZERO,
+            glow: Vec4::ZERO,
+            shadow_color: Vec4::ZERO,
+            shadow: Vec4::ZERO,
+            fill: Vec4::ZERO,
+            fill_start_color: Vec4::ONE,
+            fill_end_color: Vec4::ONE,
+            fill_geometry: Vec4::ZERO,
+            bevel: Vec4::ZERO,
+            bevel_light: Vec4::Z,
+            emissive: Vec4::ZERO,
+            screen_size: Vec4::ZERO,
+            alpha_cutoff: 0.5,
+            billboard: 0,
+            occluded_opacity: 1.0,
         }
     }
 
     pub fn smoothing(&self) -> f32 {
         self.params.x
     }
+
+    pub fn weight(&self) -> f32 {
+        self.params.y
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.params.z
+    }
+
+    pub fn outline_count(&self) -> usize {
+        self.params.w as usize
+    }
 }
 
 impl Default for GlyphMaterialUniform {
@@ -38,12 +214,195 @@ impl Default for GlyphMaterialUniform {
 }
 
 #[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+#[bind_group_data(GlyphMaterialKey)]
 pub struct GlyphMaterial {
     #[uniform(0)]
     pub params: GlyphMaterialUniform,
     #[texture(1)]
     #[sampler(2)]
     pub sdf_texture: Handle<Image>,
+    /// Per-glyph records read by the vertex shader when `instanced` is set.
+    /// Use [`EMPTY_GLYPH_INSTANCES`] otherwise.
+    #[storage(3, read_only, visibility(vertex))]
+    pub glyph_instances: Handle<ShaderStorageBuffer>,
+    /// [`GlyphLabel`] records of the labels merged into a batch, indexed by
+    /// [`GlyphInstance::label`], when `batched` is set. Use [`GLYPH_LABELS`] otherwise.
+    #[storage(4, read_only, visibility(vertex))]
+    pub labels: Handle<ShaderStorageBuffer>,
+    /// Image sampled by image fills; see [`GlyphMaterialUniform::fill`].
+    #[texture(5)]
+    #[sampler(6)]
+    pub fill_texture: Option<Handle<Image>>,
+    /// Expand quads from `glyph_instances` instead of reading per-vertex attributes.
+    pub instanced: bool,
+    /// Place each instanced glyph with its label's transform from `labels`.
+    /// Requires `instanced`.
+    pub batched: bool,
+    /// `Blend`, or `Mask` and `AlphaToCoverage` to draw the glyphs in the opaque pass with
+    /// depth writes, cut at [`GlyphMaterialUniform::alpha_cutoff`].
+    pub alpha_mode: AlphaMode,
+    /// Whether `Mask` and `AlphaToCoverage` glyphs are drawn forward or through the deferred
+    /// gbuffer. Blended glyphs are always drawn forward.
+    pub opaque_render_method: OpaqueRendererMethod,
+    /// How the glyphs are depth tested; part of the pipeline specialization key. Text drawn
+    /// over the scene should use `Blend`.
+    pub depth_mode: Text3dDepthMode,
+}
+
+impl GlyphMaterial {
+    /// Creates a material that reads glyph quads from vertex attributes.
+    pub fn new(sdf_texture: Handle<Image>, params: GlyphMaterialUniform) -> Self {
+        Self {
+            params,
+            sdf_texture,
+            glyph_instances: EMPTY_GLYPH_INSTANCES,
+            labels: GLYPH_LABELS,
+            fill_texture: None,
+            instanced: false,
+            batched: false,
+            alpha_mode: AlphaMode::Blend,
+            opaque_render_method: OpaqueRendererMethod::Auto,
+            depth_mode: Text3dDepthMode::Normal,
+        }
+    }
+}
+
+/// Writes `records` into `buffer` and touches `material`, whose bind group has to pick up the
+/// new GPU buffer the upload creates.
+///
+/// Storage buffers can't be empty, so no records upload a single default one instead; a
+/// zeroed glyph record has no area and draws nothing.
+pub(crate) fn upload_glyph_records<T>(
+    mut records: Vec<T>,
+    buffer: &Handle<ShaderStorageBuffer>,
+    material: &Handle<GlyphMaterial>,
+    storage_buffers: &mut Assets<ShaderStorageBuffer>,
+    materials: &mut Assets<GlyphMaterial>,
+) where
+    T: Default,
+    Vec<T>: ShaderType + WriteInto,
+{
+    if records.is_empty() {
+        records.push(T::default());
+    }
+    if let Some(storage) = storage_buffers.get_mut(buffer) {
+        storage.set_data(records);
+    }
+    materials.get_mut(material);
+}
+
+/// Records written in place into the GPU buffers of storage buffer assets.
+///
+/// [`ShaderStorageBuffer::set_data`] creates a new GPU buffer, which every material binding it
+/// has to rebuild its bind group for. Records queued here are instead copied into the existing
+/// buffer once it is prepared, so labels that only move or fade leave their materials alone.
+/// Writes are queued in the main world each frame, cleared at its start, and applied by
+/// [`write_glyph_records_system`] in the render world.
+#[derive(Clone, Default, Resource)]
+pub(crate) struct GlyphRecordWrites {
+    writes: Vec<(AssetId<ShaderStorageBuffer>, u64, Vec<u8>)>,
+}
+
+impl GlyphRecordWrites {
+    /// Creates a storage buffer holding `records` that accepts in-place writes.
+    pub(crate) fn writable_buffer<T: ShaderType + WriteInto>(records: T) -> ShaderStorageBuffer {
+        let mut buffer = ShaderStorageBuffer::from(records);
+        buffer.buffer_description.usage |= BufferUsages::COPY_DST;
+        buffer
+    }
+
+    /// Queues writing `record` over the record at `index` of `buffer`, which must have been
+    /// created by [`Self::writable_buffer`] with at least `index + 1` records.
+    pub(crate) fn write<T: ShaderType + ShaderSize + WriteInto>(
+        &mut self,
+        buffer: &Handle<ShaderStorageBuffer>,
+        index: u32,
+        record: &T,
+    ) {
+        let mut bytes = StorageBuffer::new(Vec::new());
+        bytes.write(record).expect("records fit in memory");
+        self.writes.push((
+            buffer.id(),
+            u64::from(index) * T::SHADER_SIZE.get(),
+            bytes.into_inner(),
+        ));
+    }
+
+    /// Returns the queued writes as buffer, byte offset and record bytes.
+    #[cfg(test)]
+    pub(crate) fn writes(&self) -> &[(AssetId<ShaderStorageBuffer>, u64, Vec<u8>)] {
+        &self.writes
+    }
+}
+
+/// System that drops the record writes of the previous frame.
+pub(crate) fn clear_glyph_record_writes_system(mut writes: ResMut<GlyphRecordWrites>) {
+    writes.writes.clear();
+}
+
+/// System that copies this frame's record writes into the render world.
+pub(crate) fn extract_glyph_record_writes_system(
+    mut render_writes: ResMut<GlyphRecordWrites>,
+    writes: Extract<Res<GlyphRecordWrites>>,
+) {
+    render_writes.writes.clone_from(&writes.writes);
+}
+
+/// System that applies the extracted record writes to the prepared GPU buffers. Writes past
+/// the end of a buffer, which a pending re-upload is about to replace, are skipped.
+pub(crate) fn write_glyph_records_system(
+    writes: Res<GlyphRecordWrites>,
+    buffers: Res<RenderAssets<GpuShaderStorageBuffer>>,
+    queue: Res<RenderQueue>,
+) {
+    for (id, offset, bytes) in &writes.writes {
+        let Some(gpu) = buffers.get(*id) else {
+            continue;
+        };
+        if offset + bytes.len() as u64 <= gpu.buffer.size() {
+            queue.write_buffer(&gpu.buffer, *offset, bytes);
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GlyphMaterialKey {
+    instanced: bool,
+    batched: bool,
+    depth: GlyphDepthKey,
+}
+
+impl From<&GlyphMaterial> for GlyphMaterialKey {
+    fn from(material: &GlyphMaterial) -> Self {
+        Self {
+            instanced: material.instanced,
+            batched: material.batched,
+            depth: material.depth_mode.into(),
+        }
+    }
+}
+
+/// Depth state of the glyph pipelines, specialized from a [`Text3dDepthMode`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GlyphDepthKey {
+    /// Draw over the scene instead of depth testing.
+    on_top: bool,
+    /// Dim the glyphs where the depth prepass is in front of them.
+    xray: bool,
+    /// Constant depth bias, as wgpu takes it.
+    depth_bias: i32,
+}
+
+impl From<Text3dDepthMode> for GlyphDepthKey {
+    fn from(mode: Text3dDepthMode) -> Self {
+        Self {
+            on_top: mode.draws_on_top(),
+            xray: matches!(mode, Text3dDepthMode::XRay { .. }),
+            depth_bias: mode.depth_bias() as i32,
+        }
+    }
 }
 
 impl Material for GlyphMaterial {
@@ -63,33 +422,117 @@ impl Material for GlyphMaterial {
         ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
     }
 
+    fn deferred_vertex_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        ShaderRef::Path("shaders/text3d_prepass.wgsl".into())
+    }
+
     fn alpha_mode(&self) -> AlphaMode {
-        AlphaMode::Blend
+        self.alpha_mode
+    }
+
+    fn opaque_render_method(&self) -> OpaqueRendererMethod {
+        self.opaque_render_method
+    }
+
+    fn depth_bias(&self) -> f32 {
+        self.depth_mode.depth_bias()
     }
 
     fn specialize(
         _pipeline: &bevy::pbr::MaterialPipeline,
         descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
         layout: &MeshVertexBufferLayoutRef,
-        _key: bevy::pbr::MaterialPipelineKey<Self>,
+        key: bevy::pbr::MaterialPipelineKey<Self>,
     ) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
-        // TODO: store position/uv/color per char in SSBO, instead of per vertex
-        let vertex_layout = layout.0.get_layout(&[
-            ATTRIBUTE_POSITION.at_shader_location(0),
-            Mesh::ATTRIBUTE_UV_0.at_shader_location(1),
-            Mesh::ATTRIBUTE_COLOR.at_shader_location(2),
-        ])?;
-
-        descriptor.vertex.buffers = vec![vertex_layout];
-        descriptor.primitive.cull_mode = None;
+        specialize_glyph_material(descriptor, layout, key.bind_group_data)
+    }
+}
 
-        // Set entry points - use "vertex" and "fragment" for both main and prepass
-        descriptor.vertex.entry_point = Some("vertex".into());
-        if let Some(ref mut fragment) = descriptor.fragment {
-            fragment.entry_point = Some("fragment".into());
+/// Specializes the pipelines of every pass a [`GlyphMaterial`] is drawn in, forward or
+/// deferred, for the material's `key`.
+pub(crate) fn specialize_glyph_material(
+    descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+    layout: &MeshVertexBufferLayoutRef,
+    key: GlyphMaterialKey,
+) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
+    if key.instanced {
+        // Position, UV and color come from the glyph instance buffer.
+        descriptor.vertex.shader_defs.push("GLYPH_INSTANCED".into());
+        if key.batched {
+            descriptor.vertex.shader_defs.push("GLYPH_BATCHED".into());
         }
+        descriptor.vertex.buffers = vec![
+            layout
+                .0
+                .get_layout(&[ATTRIBUTE_GLYPH_VERTEX_INDEX.at_shader_location(0)])?,
+        ];
+    } else {
+        specialize_glyph_vertices(descriptor, layout)?;
+    }
+    specialize_glyph_depth(descriptor, key.depth);
+    descriptor.primitive.cull_mode = None;
 
-        Ok(())
+    // Set entry points - use "vertex" and "fragment" for both main and prepass
+    descriptor.vertex.entry_point = Some("vertex".into());
+    if let Some(ref mut fragment) = descriptor.fragment {
+        fragment.entry_point = Some("fragment".into());
+    }
+
+    Ok(())
+}
+
+/// Sets the vertex buffer layout of glyph meshes built from vertex attributes, at the shader
+/// locations the glyph shaders read them from. Meshes with emissive glyphs also enable
+/// `GLYPH_EMISSIVE`.
+pub(crate) fn specialize_glyph_vertices(
+    descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+    layout: &MeshVertexBufferLayoutRef,
+) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
+    let mut attributes = vec![
+        ATTRIBUTE_POSITION.at_shader_location(0),
+        ATTRIBUTE_UV.at_shader_location(1),
+        ATTRIBUTE_COLOR.at_shader_location(2),
+        ATTRIBUTE_GLYPH_COORD.at_shader_location(3),
+    ];
+    if layout.0.contains(ATTRIBUTE_GLYPH_EMISSIVE) {
+        descriptor.vertex.shader_defs.push("GLYPH_EMISSIVE".into());
+        attributes.push(ATTRIBUTE_GLYPH_EMISSIVE.at_shader_location(4));
+    }
+    descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+    Ok(())
+}
+
+/// Applies the depth mode of `key` to the main pass pipeline of glyphs. Prepasses and shadows
+/// are left untouched: they keep testing depth without the decal bias, so text drawn over the
+/// scene still casts shadows from where it is.
+pub(crate) fn specialize_glyph_depth(
+    descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+    key: GlyphDepthKey,
+) {
+    let prepass = descriptor
+        .vertex
+        .shader_defs
+        .contains(&"PREPASS_PIPELINE".into());
+    if prepass {
+        return;
+    }
+    let Some(depth_stencil) = descriptor.depth_stencil.as_mut() else {
+        return;
+    };
+    depth_stencil.bias.constant = key.depth_bias;
+    if !key.on_top {
+        return;
+    }
+    depth_stencil.depth_compare = CompareFunction::Always;
+    depth_stencil.depth_write_enabled = false;
+    if key.xray
+        && let Some(fragment) = descriptor.fragment.as_mut()
+    {
+        fragment.shader_defs.push("GLYPH_XRAY".into());
     }
 }
 
@@ -0,0 +1,1320 @@
+//! Per-entity appearance of SDF text and the glyph materials shared between equal styles.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+use bevy::{
+    asset::{AssetId, Assets, Handle},
+    camera::Camera3d,
+    color::{Color, ColorToComponents},
+    core_pipeline::prepass::DepthPrepass,
+    math::{Vec2, Vec3, Vec4},
+    pbr::OpaqueRendererMethod,
+    prelude::{
+        Added, AlphaMode, Changed, Commands, Component, DetectChanges, DetectChangesMut, Entity,
+        Image, MeshMaterial3d, Query, RemovedComponents, Res, ResMut, Resource, With, Without,
+    },
+    render::storage::ShaderStorageBuffer,
+};
+use bevy_log::{debug, warn_once};
+use bevy_mesh::MeshTag;
+
+use crate::{
+    batching::Text3dBatched,
+    font::{FontAtlasSets, SdfTier},
+    lit::LitGlyphMaterial,
+    pipeline::{QuadBounds, Text3d},
+    pipeline_material::{
+        GLYPH_LABELS, GlyphLabel, GlyphMaterial, GlyphMaterialUniform, GlyphRecordWrites,
+        MAX_GLYPH_OUTLINES,
+    },
+};
+
+/// Appearance of the SDF glyphs of a [`Text3d`], mapped onto its [`GlyphMaterial`] uniforms.
+///
+/// Changing the style only swaps or updates materials and keeps the glyph meshes. `opacity` and
+/// `tint` stay out of the shared materials: fading or tinting a label only rewrites its record
+/// in the label table. Changing how far the outlines, glow or shadow reach past the glyph edge
+/// also rebuilds the quads to make room for them. Text without a style uses
+/// `Text3dStyle::default()`.
+///
+/// Distances are in texels of the [`SdfTier::Regular`] atlases, and cover the same part of
+/// each glyph in the other tiers. The SDF only covers half of the font atlas `range` outside
+/// the glyph edge, and a shadow can't be offset further than that either, so a style reaching
+/// further is clamped with a warning.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Text3dStyle {
+    /// Scales the anti-aliasing width derived from the screen-space SDF gradient.
+    /// Values above 1.0 soften the glyph edges, values below sharpen them.
+    pub smoothing: f32,
+    /// Multiplied with the color of every glyph.
+    pub tint: Color,
+    /// Multiplied with the glyph alpha, for fading whole labels.
+    pub opacity: f32,
+    /// Moves the glyph edge outwards (dilate) by this many texels, or inwards (erode) when
+    /// negative. Outlines start at the moved edge.
+    pub weight: f32,
+    /// Outlines stacked around the glyph, innermost first. At most [`MAX_GLYPH_OUTLINES`]
+    /// are drawn.
+    pub outlines: Vec<GlyphOutline>,
+    /// Glow fading out from the outer edge of the glyph and its outlines.
+    pub glow: Option<GlyphGlow>,
+    /// Shadow drawn behind the glyph, its outlines and glow.
+    pub shadow: Option<GlyphShadow>,
+    /// Gradient or image filling the glyphs inside their outlines.
+    pub fill: GlyphFill,
+    /// Rounded bevel tilting the surface normal along the inside of the glyph edge.
+    pub bevel: Option<GlyphBevel>,
+    /// Light emitted by the glyphs, their outlines and glow as a multiple of their color,
+    /// added to each glyph's own `Glyph::emissive`. Above zero the text renders brighter than
+    /// its colors, past 1.0 on HDR cameras, where `Bloom` picks it up.
+    pub emissive: f32,
+    /// Animates the emission in the shader, without touching materials or meshes.
+    pub pulse: Option<GlyphPulse>,
+    /// How the glyphs are composited with what's behind them.
+    pub alpha_mode: GlyphAlphaMode,
+    /// Whether `Mask` and `AlphaToCoverage` glyphs are drawn forward or into the deferred
+    /// gbuffer. `Auto` follows the app's `DefaultOpaqueRendererMethod`; blended glyphs are
+    /// always drawn forward.
+    pub opaque_render_method: OpaqueRendererMethod,
+    /// Turns the text about its origin to face the camera.
+    pub billboard: Option<Text3dBillboard>,
+    /// Scales the text about its origin to a fixed height on screen.
+    pub screen_size: Option<Text3dScreenSize>,
+    /// How the glyphs are depth tested against the scene.
+    pub depth_mode: Text3dDepthMode,
+}
+
+/// How the text of a [`Text3dStyle`] turns about its origin to face the camera.
+///
+/// Applied in the vertex shader for each view, so every camera, including each half of a
+/// split screen, sees the text facing it. The entity's rotation is replaced; its translation
+/// and scale are kept. Only text drawn with [`GlyphProfileRenderMode::SdfMasked`] casts
+/// shadows while billboarded: shadow maps see its atlas quads turned towards their light, so
+/// the shadows keep the full glyph shapes. The glyph profile can't turn, so billboarded text in
+/// other modes casts no shadows.
+///
+/// [`GlyphProfileRenderMode::SdfMasked`]: crate::pipeline::GlyphProfileRenderMode::SdfMasked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Text3dBillboard {
+    /// Faces the camera from any direction, staying upright unless seen from straight
+    /// above or below.
+    Spherical,
+    /// Turns about the world Y axis only, staying upright like a signpost.
+    Cylindrical,
+    /// Parallel to the screen, rotating with the camera.
+    ScreenAligned,
+}
+
+impl Text3dBillboard {
+    /// Returns the billboard mode index read by the glyph shaders.
+    fn index(mode: Option<Self>) -> u32 {
+        match mode {
+            None => 0,
+            Some(Self::Spherical) => 1,
+            Some(Self::Cylindrical) => 2,
+            Some(Self::ScreenAligned) => 3,
+        }
+    }
+}
+
+/// Keeps the text of a [`Text3dStyle`] at a fixed height on screen, e.g. for waypoint markers
+/// that stay readable at any distance.
+///
+/// Applied in the vertex shader for each view after any [`Text3dBillboard`], scaling the text
+/// uniformly about its origin; the entity's scale is replaced. The glyphs are drawn from the
+/// [`SdfTier`] matching `pixel_height`, taken as pixels per em. Text the world height limits
+/// shrink or grow on screen keeps that tier, its edges anti-aliased from screen space
+/// derivatives. Screen sized text casts no shadows, as its size only holds for the camera,
+/// and isn't frustum culled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Text3dScreenSize {
+    /// Height in pixels of one text space unit.
+    pub pixel_height: f32,
+    /// Smallest world size of one text space unit, keeping close text from shrinking below it.
+    pub min_world_height: Option<f32>,
+    /// Largest world size of one text space unit, letting distant text shrink on screen.
+    pub max_world_height: Option<f32>,
+}
+
+impl Text3dScreenSize {
+    /// Keeps one text space unit `pixel_height` pixels high, at any world size.
+    pub fn new(pixel_height: f32) -> Self {
+        Self {
+            pixel_height,
+            min_world_height: None,
+            max_world_height: None,
+        }
+    }
+
+    /// Keeps one text space unit between `min` and `max` world units high.
+    pub fn with_world_height(mut self, min: f32, max: f32) -> Self {
+        self.min_world_height = Some(min);
+        self.max_world_height = Some(max);
+        self
+    }
+
+    /// Returns the screen size uniform read by the glyph shaders.
+    fn params(size: Option<Self>) -> Vec4 {
+        size.map_or(Vec4::ZERO, |size| {
+            Vec4::new(
+                size.pixel_height.max(0.0),
+                size.min_world_height.unwrap_or(0.0).max(0.0),
+                size.max_world_height.unwrap_or(0.0).max(0.0),
+                0.0,
+            )
+        })
+    }
+}
+
+/// How the glyphs of a [`Text3dStyle`] are depth tested against the scene.
+///
+/// Selected per material through the pipeline specialization key, so text with different
+/// modes never shares a pipeline or a batch. `AlwaysOnTop` and `XRay` text is drawn blended
+/// after the opaque scene whatever the style's [`GlyphAlphaMode`], and is sorted with other
+/// transparent meshes. Depth prepasses and shadows always test depth normally, without the
+/// decal bias.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Text3dDepthMode {
+    /// Hidden behind whatever is in front of it.
+    #[default]
+    Normal,
+    /// Drawn over the scene without depth testing, e.g. for markers that must stay visible.
+    AlwaysOnTop,
+    /// Drawn over the scene like `AlwaysOnTop`, but multiplied by `occluded_opacity` where
+    /// other surfaces hide it. Reads the depth prepass, so the camera needs a
+    /// `DepthPrepass`; without one the text is drawn like `AlwaysOnTop`, and a warning is
+    /// logged once.
+    XRay { occluded_opacity: f32 },
+    /// Depth tested with its depth pulled towards the camera by `depth_bias`, so text laid
+    /// flush on a wall doesn't z-fight with it. Same units as
+    /// [`StandardMaterial::depth_bias`](bevy::pbr::StandardMaterial::depth_bias).
+    SurfaceDecal { depth_bias: f32 },
+}
+
+impl Text3dDepthMode {
+    /// Returns whether the glyphs are drawn over the scene instead of depth tested.
+    pub(crate) fn draws_on_top(self) -> bool {
+        matches!(self, Self::AlwaysOnTop | Self::XRay { .. })
+    }
+
+    /// Returns the depth bias of the glyph pipelines.
+    pub(crate) fn depth_bias(self) -> f32 {
+        match self {
+            Self::SurfaceDecal { depth_bias } => depth_bias,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the mode index and its parameter, for style keys.
+    fn key(self) -> [f32; 2] {
+        match self {
+            Self::Normal => [0.0, 0.0],
+            Self::AlwaysOnTop => [1.0, 0.0],
+            Self::XRay { occluded_opacity } => [2.0, occluded_opacity],
+            Self::SurfaceDecal { depth_bias } => [3.0, depth_bias],
+        }
+    }
+}
+
+/// How the glyphs of a [`Text3dStyle`] are composited with what's behind them.
+///
+/// Blended text is sorted per entity and doesn't write depth, so overlapping labels can draw
+/// in the wrong order and SSAO or depth of field ignore them. The other modes draw in the
+/// opaque pass, sorted by depth per fragment, at the cost of hard or dithered edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GlyphAlphaMode {
+    /// Alpha blended over the scene, with smooth anti-aliased edges.
+    #[default]
+    Blend,
+    /// Opaque wherever the glyph alpha reaches the threshold and discarded elsewhere.
+    /// `0.5` cuts at the anti-aliased edge of the outermost layer; glows and shadows are cut
+    /// where they fade below the threshold. `opacity` only moves the cut.
+    Mask(f32),
+    /// Turns the glyph alpha into MSAA sample coverage, keeping edges anti-aliased while
+    /// writing depth. Without MSAA it falls back to `Mask(0.5)`.
+    AlphaToCoverage,
+}
+
+impl From<GlyphAlphaMode> for AlphaMode {
+    fn from(mode: GlyphAlphaMode) -> Self {
+        match mode {
+            GlyphAlphaMode::Blend => AlphaMode::Blend,
+            GlyphAlphaMode::Mask(threshold) => AlphaMode::Mask(threshold),
+            GlyphAlphaMode::AlphaToCoverage => AlphaMode::AlphaToCoverage,
+        }
+    }
+}
+
+/// How the fill of a [`Text3dStyle`] colors its glyphs. The fill is multiplied with the glyph
+/// colors and `tint`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum GlyphFill {
+    /// Glyph colors only.
+    #[default]
+    Solid,
+    /// Blends from `start_color` at `start` to `end_color` at `end`.
+    LinearGradient {
+        start: Vec2,
+        end: Vec2,
+        start_color: Color,
+        end_color: Color,
+        space: FillSpace,
+    },
+    /// Blends from `center_color` at `center` to `edge_color` at `radius` from it.
+    RadialGradient {
+        center: Vec2,
+        radius: f32,
+        center_color: Color,
+        edge_color: Color,
+        space: FillSpace,
+    },
+    /// Samples `image` upright, with its top left corner at `(0, 1)` of the fill space and
+    /// `scale` images per unit. Give the image a repeating sampler to tile it.
+    Image {
+        image: Handle<Image>,
+        scale: Vec2,
+        offset: Vec2,
+        space: FillSpace,
+    },
+}
+
+/// Coordinates a [`GlyphFill`] is defined in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillSpace {
+    /// Text space shared by all glyphs of a [`Text3d`], in the units of glyph positions.
+    #[default]
+    Text,
+    /// Each glyph quad, from `(0, 0)` at its min corner to `(1, 1)` at its max corner.
+    Glyph,
+}
+
+/// An outline drawn behind the glyph and the outlines inside it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphOutline {
+    /// Width in atlas texels.
+    pub width: f32,
+    pub color: Color,
+}
+
+/// An outer glow computed from the glyph SDF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphGlow {
+    /// Distance in atlas texels over which the glow fades out.
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// A soft drop shadow computed from the glyph SDF, without lights or glyph profile meshes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphShadow {
+    /// Offset in atlas texels along the text's X and Y axes.
+    pub offset: Vec2,
+    /// Distance in atlas texels over which the shadow edge fades out.
+    pub softness: f32,
+    pub color: Color,
+}
+
+/// A faux bevel derived from the glyph SDF, embossing the fill without extra geometry.
+///
+/// Lit text (see `Text3dLit`) is shaded by the scene lights along the bevel normal; the unlit
+/// [`GlyphMaterial`] shades it with `light_direction` instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphBevel {
+    /// Distance in atlas texels from the glyph edge to the flat top of the bevel.
+    pub width: f32,
+    /// Height of the bevel top above the glyph edge, in atlas texels.
+    pub height: f32,
+    /// Direction towards the light shading the bevel of unlit text, in text space.
+    pub light_direction: Vec3,
+}
+
+/// Periodic and random dimming of the emission of a [`Text3dStyle`], e.g. for neon signs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphPulse {
+    /// Pulses per second.
+    pub frequency: f32,
+    /// Fraction of the emission faded out at the low point of each pulse, 0.0 to 1.0.
+    pub depth: f32,
+    /// Largest fraction of the emission randomly dropped by flickering, 0.0 to 1.0.
+    pub flicker: f32,
+}
+
+impl Default for Text3dStyle {
+    fn default() -> Self {
+        Self {
+            smoothing: 1.0,
+            tint: Color::WHITE,
+            opacity: 1.0,
+            weight: 0.0,
+            outlines: Vec::new(),
+            glow: None,
+            shadow: None,
+            fill: GlyphFill::Solid,
+            bevel: None,
+            emissive: 0.0,
+            pulse: None,
+            alpha_mode: GlyphAlphaMode::Blend,
+            opaque_render_method: OpaqueRendererMethod::Auto,
+            billboard: None,
+            screen_size: None,
+            depth_mode: Text3dDepthMode::Normal,
+        }
+    }
+}
+
+/// Style of text without a [`Text3dStyle`], borrowed by systems instead of building one per
+/// entity.
+pub(crate) static DEFAULT_STYLE: LazyLock<Text3dStyle> = LazyLock::new(Text3dStyle::default);
+
+impl Text3dStyle {
+    /// Adds an outline of `width` texels around the existing ones.
+    pub fn with_outline(mut self, width: f32, color: impl Into<Color>) -> Self {
+        self.outlines.push(GlyphOutline {
+            width,
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Adds a glow fading out over `radius` texels.
+    pub fn with_glow(mut self, radius: f32, color: impl Into<Color>) -> Self {
+        self.glow = Some(GlyphGlow {
+            radius,
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Adds a drop shadow moved by `offset` texels with edges softened over `softness` texels.
+    pub fn with_shadow(mut self, offset: Vec2, softness: f32, color: impl Into<Color>) -> Self {
+        self.shadow = Some(GlyphShadow {
+            offset,
+            softness,
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Adds a bevel `width` texels wide and `height` texels high, lit from the top left when
+    /// unlit.
+    pub fn with_bevel(mut self, width: f32, height: f32) -> Self {
+        self.bevel = Some(GlyphBevel {
+            width,
+            height,
+            light_direction: Vec3::new(-1.0, 1.0, 2.0),
+        });
+        self
+    }
+
+    /// Makes the text emit `emissive` times its colors.
+    pub fn with_emissive(mut self, emissive: f32) -> Self {
+        self.emissive = emissive;
+        self
+    }
+
+    /// Pulses the emission `frequency` times per second, fading out `depth` of it at each
+    /// low point, and randomly drops up to `flicker` of it.
+    pub fn with_pulse(mut self, frequency: f32, depth: f32, flicker: f32) -> Self {
+        self.pulse = Some(GlyphPulse {
+            frequency,
+            depth,
+            flicker,
+        });
+        self
+    }
+
+    /// Turns the text to face the camera.
+    pub fn with_billboard(mut self, billboard: Text3dBillboard) -> Self {
+        self.billboard = Some(billboard);
+        self
+    }
+
+    /// Keeps the text at a fixed height on screen.
+    pub fn with_screen_size(mut self, screen_size: Text3dScreenSize) -> Self {
+        self.screen_size = Some(screen_size);
+        self
+    }
+
+    /// Depth tests the glyphs as `depth_mode` asks.
+    pub fn with_depth_mode(mut self, depth_mode: Text3dDepthMode) -> Self {
+        self.depth_mode = depth_mode;
+        self
+    }
+
+    /// Draws `Mask` and `AlphaToCoverage` glyphs with `method`.
+    pub fn with_opaque_render_method(mut self, method: OpaqueRendererMethod) -> Self {
+        self.opaque_render_method = method;
+        self
+    }
+
+    /// Fills the glyphs with `fill`.
+    pub fn with_fill(mut self, fill: GlyphFill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Returns how far, in texels, the style draws past the glyph edge.
+    pub fn reach(&self) -> f32 {
+        self.extents(f32::INFINITY).padding
+    }
+
+    /// Returns the texels glyph quads must grow by to fit the style in an atlas with the
+    /// given SDF `range`.
+    pub(crate) fn padding(&self, range: u8) -> u8 {
+        (self.extents(STYLE_RANGE).padding * texel_scale(range)).ceil() as u8
+    }
+
+    /// Returns the tier of the atlases the glyphs are drawn from.
+    pub(crate) fn sdf_tier(&self) -> SdfTier {
+        self.screen_size.map_or(SdfTier::Regular, |size| {
+            SdfTier::for_pixel_height(size.pixel_height)
+        })
+    }
+
+    /// Returns the material uniforms for this style, for an atlas with the given SDF `range`.
+    pub fn uniform(&self, range: u8) -> GlyphMaterialUniform {
+        let extents = self.extents(STYLE_RANGE);
+        if extents.clamped {
+            warn_once!(
+                "Text3dStyle reaches {} texels from the glyph edge, but the atlas range of {} only covers {}; clamping",
+                self.reach().max(-self.weight),
+                STYLE_RANGE,
+                max_reach(STYLE_RANGE)
+            );
+        }
+        if self.outlines.len() > MAX_GLYPH_OUTLINES {
+            warn_once!(
+                "Text3dStyle has {} outlines; only the first {} are drawn",
+                self.outlines.len(),
+                MAX_GLYPH_OUTLINES
+            );
+        }
+
+        // Offsets are normalized so the atlas range spans 1.0.
+        let normalize = |texels: f32| texels / STYLE_RANGE;
+        // Opacity is applied per label, so materials can be shared across labels.
+        let mut uniform = GlyphMaterialUniform {
+            params: Vec4::new(self.smoothing, normalize(extents.weight), 1.0, 0.0),
+            ..Default::default()
+        };
+        for (i, (outline, edge)) in self.outlines.iter().zip(extents.outline_edges).enumerate() {
+            uniform.outline_colors[i] = outline.color.to_linear().to_vec4();
+            uniform.outline_edges[i] = normalize(edge);
+            uniform.params.w += 1.0;
+        }
+        if let Some(glow) = &self.glow {
+            uniform.glow_color = glow.color.to_linear().to_vec4();
+            uniform.glow.x = normalize(extents.glow_radius);
+        }
+        if let Some(shadow) = &self.shadow {
+            uniform.shadow_color = shadow.color.to_linear().to_vec4();
+            uniform.shadow = (extents.shadow_offset * texel_scale(range))
+                .extend(normalize(extents.shadow_softness))
+                .extend(0.0);
+        }
+        if let Some(bevel) = &self.bevel
+            && extents.bevel_width > 0.0
+        {
+            uniform.bevel = Vec4::new(
+                normalize(extents.bevel_width),
+                bevel.height.max(0.0) / extents.bevel_width,
+                0.0,
+                0.0,
+            );
+            uniform.bevel_light = bevel.light_direction.normalize_or(Vec3::Z).extend(0.0);
+        }
+        if let GlyphAlphaMode::Mask(threshold) = self.alpha_mode {
+            uniform.alpha_cutoff = threshold;
+        }
+        uniform.billboard = Text3dBillboard::index(self.billboard);
+        uniform.screen_size = Text3dScreenSize::params(self.screen_size);
+        if let Text3dDepthMode::XRay { occluded_opacity } = self.depth_mode {
+            uniform.occluded_opacity = occluded_opacity.clamp(0.0, 1.0);
+        }
+        uniform.emissive.x = self.emissive;
+        if let Some(pulse) = &self.pulse {
+            uniform.emissive.y = pulse.frequency;
+            uniform.emissive.z = pulse.depth.clamp(0.0, 1.0);
+            uniform.emissive.w = pulse.flicker.clamp(0.0, 1.0);
+        }
+        [
+            uniform.fill,
+            uniform.fill_start_color,
+            uniform.fill_end_color,
+            uniform.fill_geometry,
+        ] = self.fill_params();
+        uniform
+    }
+
+    /// Returns the fill mode, start and end colors and geometry uniforms of the fill.
+    fn fill_params(&self) -> [Vec4; 4] {
+        match &self.fill {
+            GlyphFill::Solid => [Vec4::ZERO, Vec4::ONE, Vec4::ONE, Vec4::ZERO],
+            GlyphFill::LinearGradient {
+                start,
+                end,
+                start_color,
+                end_color,
+                space,
+            } => [
+                Vec4::new(1.0, fill_space(*space), 0.0, 0.0),
+                start_color.to_linear().to_vec4(),
+                end_color.to_linear().to_vec4(),
+                start.extend(end.x).extend(end.y),
+            ],
+            GlyphFill::RadialGradient {
+                center,
+                radius,
+                center_color,
+                edge_color,
+                space,
+            } => [
+                Vec4::new(2.0, fill_space(*space), 0.0, 0.0),
+                center_color.to_linear().to_vec4(),
+                edge_color.to_linear().to_vec4(),
+                center.extend(*radius).extend(0.0),
+            ],
+            GlyphFill::Image {
+                scale,
+                offset,
+                space,
+                ..
+            } => [
+                Vec4::new(3.0, fill_space(*space), 0.0, 0.0),
+                Vec4::ONE,
+                Vec4::ONE,
+                scale.extend(offset.x).extend(offset.y),
+            ],
+        }
+    }
+
+    /// Returns the image sampled by an image fill.
+    pub fn fill_image(&self) -> Option<&Handle<Image>> {
+        match &self.fill {
+            GlyphFill::Image { image, .. } => Some(image),
+            _ => None,
+        }
+    }
+
+    /// Returns a material drawing glyphs from vertex attributes with this style, for an atlas
+    /// with the given SDF `range`.
+    pub fn material(&self, sdf_texture: Handle<Image>, range: u8) -> GlyphMaterial {
+        GlyphMaterial {
+            fill_texture: self.fill_image().cloned(),
+            alpha_mode: self.pass_alpha_mode(),
+            opaque_render_method: self.opaque_render_method,
+            depth_mode: self.depth_mode,
+            ..GlyphMaterial::new(sdf_texture, self.uniform(range))
+        }
+    }
+
+    /// Returns the alpha mode the glyph materials are drawn with, which is blended for text
+    /// drawn over the scene.
+    pub(crate) fn pass_alpha_mode(&self) -> AlphaMode {
+        if self.depth_mode.draws_on_top() {
+            AlphaMode::Blend
+        } else {
+            self.alpha_mode.into()
+        }
+    }
+
+    /// Returns the distances of the style's effects from the glyph edge, clamped to what
+    /// an atlas with the given SDF `range` represents.
+    fn extents(&self, range: f32) -> StyleExtents {
+        let limit = max_reach(range);
+        let mut clamped = false;
+        let mut clamp = |texels: f32, max: f32| {
+            clamped |= texels.abs() > max;
+            texels.clamp(-max, max)
+        };
+
+        let weight = clamp(self.weight, limit);
+        let mut outline_edges = [0.0; MAX_GLYPH_OUTLINES];
+        let mut edge = weight;
+        for (i, outline) in self.outlines.iter().take(MAX_GLYPH_OUTLINES).enumerate() {
+            edge = clamp(edge + outline.width.max(0.0), limit);
+            outline_edges[i] = edge;
+        }
+        let mut padding = edge.max(0.0);
+
+        let mut glow_radius = 0.0;
+        if let Some(glow) = &self.glow {
+            glow_radius = clamp(edge + glow.radius.max(0.0), limit) - edge;
+            padding = padding.max(edge + glow_radius);
+        }
+
+        let mut shadow_offset = Vec2::ZERO;
+        let mut shadow_softness = 0.0;
+        if let Some(shadow) = &self.shadow {
+            shadow_offset = Vec2::new(clamp(shadow.offset.x, limit), clamp(shadow.offset.y, limit));
+            shadow_softness = clamp(edge + shadow.softness.max(0.0), limit) - edge;
+            // The shadow is sampled `shadow_offset` away from the quad, which must stay
+            // within the glyph's own padding in the atlas.
+            let offset = shadow_offset.abs().max_element();
+            padding = padding.max(clamp(offset + edge + shadow_softness, range - offset));
+        }
+
+        // The SDF covers as much inside the glyph edge as outside it.
+        let bevel_width = self
+            .bevel
+            .map_or(0.0, |bevel| clamp(bevel.width.max(0.0), limit));
+
+        StyleExtents {
+            weight,
+            outline_edges,
+            glow_radius,
+            shadow_offset,
+            shadow_softness,
+            bevel_width,
+            padding,
+            clamped,
+        }
+    }
+
+    /// Returns the tint multiplied with the fill of each label, in linear RGBA.
+    pub(crate) fn linear_tint(&self) -> Vec4 {
+        self.tint.to_linear().to_vec4()
+    }
+
+    /// Returns a hashable key that is equal for styles producing the same material, which
+    /// leaves `opacity` and `tint` to each label.
+    pub(crate) fn key(&self) -> StyleKey {
+        let outlines = self.outlines.iter().flat_map(|outline| {
+            let [r, g, b, a] = outline.color.to_linear().to_f32_array();
+            [outline.width, r, g, b, a]
+        });
+        let glow = self.glow.iter().flat_map(|glow| {
+            let [r, g, b, a] = glow.color.to_linear().to_f32_array();
+            [glow.radius, r, g, b, a]
+        });
+        let shadow = self.shadow.iter().flat_map(|shadow| {
+            let [r, g, b, a] = shadow.color.to_linear().to_f32_array();
+            [
+                shadow.offset.x,
+                shadow.offset.y,
+                shadow.softness,
+                r,
+                g,
+                b,
+                a,
+            ]
+        });
+        let (alpha_mode, alpha_cutoff) = match self.alpha_mode {
+            GlyphAlphaMode::Blend => (0.0, 0.0),
+            GlyphAlphaMode::Mask(threshold) => (1.0, threshold),
+            GlyphAlphaMode::AlphaToCoverage => (2.0, 0.0),
+        };
+        let opaque_render_method = match self.opaque_render_method {
+            OpaqueRendererMethod::Auto => 0.0,
+            OpaqueRendererMethod::Forward => 1.0,
+            OpaqueRendererMethod::Deferred => 2.0,
+        };
+        let pulse = self
+            .pulse
+            .iter()
+            .flat_map(|pulse| [pulse.frequency, pulse.depth, pulse.flicker]);
+        let bevel = self.bevel.iter().flat_map(|bevel| {
+            let [x, y, z] = bevel.light_direction.to_array();
+            [bevel.width, bevel.height, x, y, z]
+        });
+        StyleKey(
+            [
+                self.smoothing,
+                self.weight,
+                self.emissive,
+                alpha_mode,
+                alpha_cutoff,
+                opaque_render_method,
+                Text3dBillboard::index(self.billboard) as f32,
+            ]
+            .into_iter()
+            .chain(self.depth_mode.key())
+            .chain(Text3dScreenSize::params(self.screen_size).to_array())
+            .chain(outlines)
+            // Keeps styles with different sets of effects from producing the same key.
+            .chain([
+                self.outlines.len() as f32,
+                self.glow.is_some() as u8 as f32,
+                self.shadow.is_some() as u8 as f32,
+                self.bevel.is_some() as u8 as f32,
+                self.pulse.is_some() as u8 as f32,
+            ])
+            .chain(glow)
+            .chain(shadow)
+            .chain(bevel)
+            .chain(pulse)
+            .chain(
+                self.fill_params()
+                    .into_iter()
+                    .flat_map(|params| params.to_array()),
+            )
+            .map(f32::to_bits)
+            .collect(),
+            self.fill_image().map(Handle::id),
+        )
+    }
+}
+
+/// SDF range of the atlases style distances are measured in.
+const STYLE_RANGE: f32 = SdfTier::Regular.range() as f32;
+
+/// Returns the atlas texels per style texel in an atlas with the given SDF `range`. The
+/// tiers grow the range with the glyph size, so the ratio is the same along every glyph.
+fn texel_scale(range: u8) -> f32 {
+    range as f32 / STYLE_RANGE
+}
+
+/// Furthest distance from the glyph edge, in texels, that the SDF of an atlas with the given
+/// `range` represents.
+fn max_reach(range: f32) -> f32 {
+    range * 0.5
+}
+
+fn fill_space(space: FillSpace) -> f32 {
+    match space {
+        FillSpace::Text => 0.0,
+        FillSpace::Glyph => 1.0,
+    }
+}
+
+/// Distances of the effects of a [`Text3dStyle`] from the glyph edge, in texels.
+struct StyleExtents {
+    weight: f32,
+    outline_edges: [f32; MAX_GLYPH_OUTLINES],
+    glow_radius: f32,
+    shadow_offset: Vec2,
+    shadow_softness: f32,
+    bevel_width: f32,
+    // How far the quads must grow around the glyphs
+    padding: f32,
+    // Whether any distance was reduced to fit the atlas range
+    clamped: bool,
+}
+
+/// Hashable form of a [`Text3dStyle`], used to share materials and batches between equal styles.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct StyleKey(Box<[u32]>, Option<AssetId<Image>>);
+
+/// Glyph materials shared by all non-instanced text with the same atlas texture and style.
+///
+/// Only asset ids are kept, so a material is freed once no text uses it anymore, and its entry
+/// is dropped the next time a material is created.
+#[derive(Default, Resource)]
+pub struct GlyphMaterialCache {
+    materials: HashMap<(AssetId<Image>, StyleKey), AssetId<GlyphMaterial>>,
+}
+
+impl GlyphMaterialCache {
+    /// Returns the shared material for `sdf_texture` drawn with `style`, creating it if needed.
+    /// `range` is the SDF range of the font atlas owning the texture.
+    pub(crate) fn material(
+        &mut self,
+        sdf_texture: &Handle<Image>,
+        style: &Text3dStyle,
+        range: u8,
+        materials: &mut Assets<GlyphMaterial>,
+    ) -> Handle<GlyphMaterial> {
+        let key = (sdf_texture.id(), style.key());
+        if let Some(handle) = self
+            .materials
+            .get(&key)
+            .and_then(|id| materials.get_strong_handle(*id))
+        {
+            return handle;
+        }
+        self.materials.retain(|_, id| materials.contains(*id));
+        let handle = materials.add(style.material(sdf_texture.clone(), range));
+        self.materials.insert(key, handle.id());
+        handle
+    }
+}
+
+/// Tint and opacity of every unbatched [`Text3d`], uploaded to [`GLYPH_LABELS`].
+///
+/// Each text gets a slot when its atlas children are spawned, and its children carry the slot
+/// in their [`MeshTag`]. Fading or tinting the text rewrites its record in place and leaves its
+/// materials and meshes alone. Slot 0 is never handed out and keeps the default record, so
+/// untagged meshes draw untinted and opaque.
+#[derive(Resource)]
+pub struct GlyphLabels {
+    records: Vec<GlyphLabel>,
+    slots: HashMap<Entity, u32>,
+    free: Vec<u32>,
+    // Slots whose records changed since the last upload
+    dirty: HashSet<u32>,
+    // Set when the table outgrew the buffer it was last uploaded to
+    grown: bool,
+}
+
+impl Default for GlyphLabels {
+    fn default() -> Self {
+        Self {
+            records: vec![GlyphLabel::default(); Self::MIN_CAPACITY],
+            slots: HashMap::new(),
+            free: (1..Self::MIN_CAPACITY as u32).rev().collect(),
+            dirty: HashSet::new(),
+            grown: false,
+        }
+    }
+}
+
+impl GlyphLabels {
+    /// Records the table starts out with; it doubles whenever it runs out of slots.
+    const MIN_CAPACITY: usize = 64;
+
+    /// Returns the tag of the meshes of `entity`, giving it a slot if it has none, and writes
+    /// the tint and opacity of `style` into its record.
+    pub(crate) fn tag(&mut self, entity: Entity, style: &Text3dStyle) -> MeshTag {
+        let slot = match self.slots.get(&entity) {
+            Some(&slot) => slot,
+            None => {
+                let slot = self.free.pop().unwrap_or_else(|| self.grow());
+                self.slots.insert(entity, slot);
+                slot
+            }
+        };
+        self.write(slot, style);
+        MeshTag(slot)
+    }
+
+    /// Writes the tint and opacity of `style` into the record of `entity`, if it has a slot.
+    pub(crate) fn update(&mut self, entity: Entity, style: &Text3dStyle) {
+        if let Some(&slot) = self.slots.get(&entity) {
+            self.write(slot, style);
+        }
+    }
+
+    /// Frees the slot of `entity`, once its meshes are gone.
+    pub(crate) fn release(&mut self, entity: Entity) {
+        if let Some(slot) = self.slots.remove(&entity) {
+            self.free.push(slot);
+        }
+    }
+
+    /// Returns the record of `entity`, if it has a slot.
+    #[cfg(test)]
+    pub(crate) fn get(&self, entity: Entity) -> Option<&GlyphLabel> {
+        let slot = self.slots.get(&entity)?;
+        self.records.get(*slot as usize)
+    }
+
+    /// Returns a buffer holding the current records, which accepts in-place writes.
+    pub(crate) fn buffer(&self) -> ShaderStorageBuffer {
+        GlyphRecordWrites::writable_buffer(self.records.clone())
+    }
+
+    fn write(&mut self, slot: u32, style: &Text3dStyle) {
+        let record = &mut self.records[slot as usize];
+        let tint = style.linear_tint();
+        if record.tint != tint || record.opacity != style.opacity {
+            record.tint = tint;
+            record.opacity = style.opacity;
+            self.dirty.insert(slot);
+        }
+    }
+
+    /// Doubles the number of slots and returns the first new one.
+    fn grow(&mut self) -> u32 {
+        let len = self.records.len() as u32;
+        self.records
+            .resize(self.records.len() * 2, GlyphLabel::default());
+        self.free.extend((len + 1..len * 2).rev());
+        self.grown = true;
+        len
+    }
+}
+
+/// System that uploads the [`GlyphLabels`] records changed this frame. They are written in
+/// place, unless the table grew and every material reading it has to bind the new buffer.
+pub fn upload_glyph_labels_system(
+    mut labels: ResMut<GlyphLabels>,
+    mut record_writes: ResMut<GlyphRecordWrites>,
+    mut storage_buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    mut materials: ResMut<Assets<GlyphMaterial>>,
+    mut lit_materials: ResMut<Assets<LitGlyphMaterial>>,
+) {
+    if !labels.is_changed() {
+        return;
+    }
+    let labels = labels.bypass_change_detection();
+    if labels.grown {
+        if let Some(storage) = storage_buffers.get_mut(&GLYPH_LABELS) {
+            storage.set_data(labels.records.clone());
+        }
+        let unbatched: Vec<_> = materials
+            .iter()
+            .filter(|(_, material)| !material.batched)
+            .map(|(id, _)| id)
+            .collect();
+        for id in unbatched {
+            materials.get_mut(id);
+        }
+        let lit: Vec<_> = lit_materials.ids().collect();
+        for id in lit {
+            lit_materials.get_mut(id);
+        }
+        labels.grown = false;
+        debug!(
+            "Grew the Text3d label table to {} slots",
+            labels.records.len()
+        );
+    } else {
+        for &slot in &labels.dirty {
+            record_writes.write(&GLYPH_LABELS, slot, &labels.records[slot as usize]);
+        }
+    }
+    labels.dirty.clear();
+}
+
+/// System that applies changed or removed [`Text3dStyle`]s to the atlas children of unbatched
+/// [`Text3d`] entities. Batched text is regrouped by `update_text_batches_system` instead.
+///
+/// Changes to `opacity` and `tint` alone only rewrite the label's [`GlyphLabels`] record.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_text3d_style_system(
+    mut commands: Commands,
+    changed: Query<Entity, (Changed<Text3dStyle>, Without<Text3dBatched>)>,
+    mut text_query: Query<(&mut Text3d, Option<&Text3dStyle>), Without<Text3dBatched>>,
+    children: Query<&MeshMaterial3d<GlyphMaterial>>,
+    mut removed: RemovedComponents<Text3dStyle>,
+    mut cache: ResMut<GlyphMaterialCache>,
+    mut materials: ResMut<Assets<GlyphMaterial>>,
+    mut labels: ResMut<GlyphLabels>,
+    font_atlas: Res<FontAtlasSets>,
+) {
+    let restyled: Vec<Entity> = changed.iter().chain(removed.read()).collect();
+    for entity in restyled {
+        let Ok((mut text_mesh, style)) = text_query.get_mut(entity) else {
+            continue;
+        };
+        let style = style.unwrap_or(&DEFAULT_STYLE);
+        labels.update(entity, style);
+        let key = style.key();
+        if text_mesh.material_key() == Some(&key) {
+            continue;
+        }
+        let Some(data) = text_mesh.atlas_set(&font_atlas) else {
+            continue;
+        };
+        // Quads only need rebuilding when they, or their culling bounds, have to grow or
+        // shrink around the glyphs.
+        if style.padding(data.range()) != text_mesh.quad_padding()
+            || QuadBounds::of(Some(style)) != text_mesh.quad_bounds()
+        {
+            text_mesh.invalidate_quads();
+        }
+        let mut uniform = None;
+        for (atlas, child, instance_material) in text_mesh.atlas_children() {
+            match instance_material {
+                // Instanced text owns its material; update it in place.
+                Some(material) => {
+                    let uniform = *uniform.get_or_insert_with(|| style.uniform(data.range()));
+                    if let Some(material) = materials.get_mut(material) {
+                        material.params = uniform;
+                        material.fill_texture = style.fill_image().cloned();
+                        material.alpha_mode = style.pass_alpha_mode();
+                        material.opaque_render_method = style.opaque_render_method;
+                        material.depth_mode = style.depth_mode;
+                    }
+                }
+                None => {
+                    let Some(sdf_texture) = data.atlas_texture(atlas) else {
+                        continue;
+                    };
+                    let material =
+                        cache.material(&sdf_texture, style, data.range(), &mut materials);
+                    if children
+                        .get(child)
+                        .is_ok_and(|current| current.0 == material)
+                    {
+                        continue;
+                    }
+                    commands.entity(child).insert(MeshMaterial3d(material));
+                }
+            }
+        }
+        // Only remembers what the materials were built for; nothing else needs to react.
+        text_mesh.bypass_change_detection().set_material_key(key);
+        debug!("Applied {:?} to Text3d ({:?})", style, entity);
+    }
+}
+
+/// System that draws each restyled [`Text3d`] from the [`SdfTier`] its style asks for,
+/// regenerating its glyphs there when the tier changes.
+pub fn select_sdf_tier_system(
+    changed: Query<Entity, Changed<Text3dStyle>>,
+    mut text_query: Query<(&mut Text3d, Option<&Text3dStyle>)>,
+    mut removed: RemovedComponents<Text3dStyle>,
+) {
+    let restyled: Vec<Entity> = changed.iter().chain(removed.read()).collect();
+    for entity in restyled {
+        let Ok((mut text_mesh, style)) = text_query.get_mut(entity) else {
+            continue;
+        };
+        let tier = style.unwrap_or(&DEFAULT_STYLE).sdf_tier();
+        if text_mesh.sdf_tier() != tier {
+            debug!("Drawing Text3d ({:?}) from {:?} atlases", entity, tier);
+            text_mesh.set_sdf_tier(tier);
+        }
+    }
+}
+
+/// System that warns once when [`Text3dDepthMode::XRay`] text is seen by a 3D camera without
+/// a [`DepthPrepass`], which draws it like `AlwaysOnTop`. Only looks at changed styles, and at
+/// every style once a camera is added.
+pub fn warn_xray_without_depth_prepass_system(
+    changed: Query<&Text3dStyle, Changed<Text3dStyle>>,
+    styles: Query<&Text3dStyle>,
+    added_cameras: Query<(), Added<Camera3d>>,
+    cameras: Query<(), (With<Camera3d>, Without<DepthPrepass>)>,
+) {
+    let is_xray = |style: &Text3dStyle| matches!(style.depth_mode, Text3dDepthMode::XRay { .. });
+    let xray = if added_cameras.is_empty() {
+        changed.iter().any(is_xray)
+    } else {
+        styles.iter().any(is_xray)
+    };
+    if xray && !cameras.is_empty() {
+        warn_once!(
+            "Text3dDepthMode::XRay needs a DepthPrepass on the camera; drawing it like AlwaysOnTop"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lit::Text3dLit;
+    use crate::pipeline_material::GlyphMaterialKey;
+    use bevy::pbr::Material;
+
+    #[test]
+    fn test_label_slots_are_reused_and_grow() {
+        let mut labels = GlyphLabels::default();
+        let first = Entity::from_raw_u32(1).unwrap();
+        let second = Entity::from_raw_u32(2).unwrap();
+        let style = Text3dStyle::default();
+
+        assert_eq!(labels.tag(first, &style).0, 1);
+        assert_eq!(labels.tag(first, &style).0, 1);
+        // An unchanged record isn't written again.
+        assert!(labels.dirty.is_empty());
+        labels.release(first);
+        assert_eq!(labels.tag(second, &style).0, 1);
+
+        let faded = Text3dStyle {
+            opacity: 0.5,
+            ..Default::default()
+        };
+        labels.update(second, &faded);
+        assert_eq!(labels.dirty, HashSet::from([1]));
+
+        for index in 3..=GlyphLabels::MIN_CAPACITY as u32 + 1 {
+            labels.tag(Entity::from_raw_u32(index).unwrap(), &style);
+        }
+        assert!(labels.grown);
+        assert_eq!(labels.records.len(), 2 * GlyphLabels::MIN_CAPACITY);
+        assert_eq!(
+            labels.slots[&Entity::from_raw_u32(GlyphLabels::MIN_CAPACITY as u32 + 1).unwrap()],
+            GlyphLabels::MIN_CAPACITY as u32
+        );
+    }
+
+    #[test]
+    fn test_material_cache_shares_equal_styles() {
+        let mut materials = Assets::<GlyphMaterial>::default();
+        let mut cache = GlyphMaterialCache::default();
+        let texture = Handle::<Image>::default();
+
+        let plain = cache.material(&texture, &Text3dStyle::default(), 6, &mut materials);
+        assert_eq!(
+            plain,
+            cache.material(&texture, &Text3dStyle::default(), 6, &mut materials)
+        );
+
+        // Opacity and tint are left to each label, so fading or tinting keeps the material.
+        let faded = Text3dStyle {
+            opacity: 0.5,
+            tint: Color::srgb(1.0, 0.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(plain, cache.material(&texture, &faded, 6, &mut materials));
+        assert_eq!(materials.get(&plain).unwrap().params.opacity(), 1.0);
+
+        let gradient = Text3dStyle::default().with_fill(GlyphFill::LinearGradient {
+            start: Vec2::ZERO,
+            end: Vec2::Y,
+            start_color: Color::BLACK,
+            end_color: Color::WHITE,
+            space: FillSpace::Glyph,
+        });
+        let gradient = cache.material(&texture, &gradient, 6, &mut materials);
+        assert_ne!(plain, gradient);
+        let params = materials.get(&gradient).unwrap().params;
+        assert_eq!(params.fill, Vec4::new(1.0, 1.0, 0.0, 0.0));
+        assert_eq!(params.fill_geometry, Vec4::new(0.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_style_outlines_fit_atlas_range() {
+        let style = Text3dStyle {
+            weight: 0.5,
+            ..Default::default()
+        }
+        .with_outline(1.0, Color::BLACK)
+        .with_outline(1.0, Color::WHITE);
+        assert_eq!(style.reach(), 2.5);
+        assert_eq!(style.padding(6), 3);
+
+        let uniform = style.uniform(6);
+        assert_eq!(uniform.outline_count(), 2);
+        assert_eq!(uniform.outline_edges.x, 1.5 / 6.0);
+        assert_eq!(uniform.outline_edges.y, 2.5 / 6.0);
+
+        // The SDF only covers half the range outside the glyph.
+        let wide = Text3dStyle::default().with_outline(10.0, Color::BLACK);
+        assert_eq!(wide.padding(6), 3);
+        assert_eq!(wide.uniform(6).outline_edges.x, 0.5);
+
+        // A shadow may reach past the SDF, as long as it's sampled within the glyph's padding.
+        let shadowed = Text3dStyle::default().with_shadow(Vec2::new(2.0, -1.0), 2.0, Color::BLACK);
+        assert_eq!(shadowed.reach(), 4.0);
+        assert_eq!(shadowed.padding(6), 4);
+        let uniform = shadowed.uniform(6);
+        assert_eq!(uniform.shadow, Vec4::new(2.0, -1.0, 2.0 / 6.0, 0.0));
+
+        // Other tiers cover the same part of each glyph in their own texels.
+        assert_eq!(shadowed.padding(SdfTier::Small.range()), 2);
+        assert_eq!(shadowed.padding(SdfTier::Large.range()), 8);
+        let small = shadowed.uniform(SdfTier::Small.range());
+        assert_eq!(small.shadow, Vec4::new(1.0, -0.5, 2.0 / 6.0, 0.0));
+        assert_eq!(
+            style.uniform(SdfTier::Large.range()).outline_edges,
+            style.uniform(6).outline_edges
+        );
+    }
+
+    #[test]
+    fn test_screen_size_style_is_unbounded() {
+        let style = Text3dStyle::default()
+            .with_billboard(Text3dBillboard::Spherical)
+            .with_screen_size(Text3dScreenSize::new(24.0).with_world_height(0.5, 10.0));
+        assert_eq!(
+            style.uniform(6).screen_size,
+            Vec4::new(24.0, 0.5, 10.0, 0.0)
+        );
+        assert_eq!(QuadBounds::of(Some(&style)), QuadBounds::Unbounded);
+        assert_eq!(QuadBounds::of(None), QuadBounds::Glyphs);
+        // Small labels are drawn from sparser atlases, large ones from denser ones.
+        assert_eq!(style.sdf_tier(), SdfTier::Small);
+        assert_eq!(Text3dStyle::default().sdf_tier(), SdfTier::Regular);
+        let large = Text3dStyle::default().with_screen_size(Text3dScreenSize::new(400.0));
+        assert_eq!(large.sdf_tier(), SdfTier::Large);
+        assert_ne!(
+            style.key(),
+            Text3dStyle::default()
+                .with_billboard(Text3dBillboard::Spherical)
+                .key()
+        );
+    }
+
+    #[test]
+    fn test_style_bevel_stays_inside_glyph() {
+        let bevelled = Text3dStyle::default().with_bevel(2.0, 1.0);
+        assert_eq!(bevelled.padding(6), 0);
+        let uniform = bevelled.uniform(6);
+        assert_eq!(uniform.bevel, Vec4::new(2.0 / 6.0, 0.5, 0.0, 0.0));
+        assert!((uniform.bevel_light.truncate().length() - 1.0).abs() < 1e-5);
+
+        // Like outlines, the bevel is limited to the half of the range the SDF covers.
+        let wide = Text3dStyle::default().with_bevel(5.0, 6.0);
+        assert_eq!(wide.uniform(6).bevel, Vec4::new(0.5, 2.0, 0.0, 0.0));
+
+        // A bevel and a glow have the same number of parameters but different materials.
+        let glowing = Text3dStyle::default().with_glow(2.0, Color::BLACK);
+        assert_ne!(bevelled.key(), glowing.key());
+    }
+
+    #[test]
+    fn test_style_alpha_mode_selects_pass() {
+        let mut materials = Assets::<GlyphMaterial>::default();
+        let mut cache = GlyphMaterialCache::default();
+        let texture = Handle::<Image>::default();
+
+        let blended = cache.material(&texture, &Text3dStyle::default(), 6, &mut materials);
+        assert_eq!(
+            materials.get(&blended).unwrap().alpha_mode,
+            AlphaMode::Blend
+        );
+
+        let masked = Text3dStyle {
+            alpha_mode: GlyphAlphaMode::Mask(0.25),
+            ..Default::default()
+        };
+        let masked_handle = cache.material(&texture, &masked, 6, &mut materials);
+        assert_ne!(blended, masked_handle);
+        let material = materials.get(&masked_handle).unwrap();
+        assert_eq!(material.alpha_mode, AlphaMode::Mask(0.25));
+        assert_eq!(material.params.alpha_cutoff, 0.25);
+        // Masked glyphs follow the app's default, which may be the deferred gbuffer.
+        assert_eq!(
+            Material::opaque_render_method(material),
+            OpaqueRendererMethod::Auto
+        );
+        let deferred = masked
+            .clone()
+            .with_opaque_render_method(OpaqueRendererMethod::Deferred);
+        let deferred_handle = cache.material(&texture, &deferred, 6, &mut materials);
+        assert_ne!(masked_handle, deferred_handle);
+        assert_eq!(
+            Material::opaque_render_method(materials.get(&deferred_handle).unwrap()),
+            OpaqueRendererMethod::Deferred
+        );
+
+        let lit = Text3dLit::default().material(&deferred, texture, 6);
+        assert_eq!(
+            lit.base.opaque_render_method,
+            OpaqueRendererMethod::Deferred
+        );
+        assert_eq!(lit.base.alpha_mode, AlphaMode::Mask(0.25));
+    }
+
+    #[test]
+    fn test_depth_mode_selects_pipeline_key() {
+        let mut materials = Assets::<GlyphMaterial>::default();
+        let mut cache = GlyphMaterialCache::default();
+        let texture = Handle::<Image>::default();
+
+        // Text drawn over the scene is blended, whatever its alpha mode.
+        let xray = Text3dStyle {
+            alpha_mode: GlyphAlphaMode::Mask(0.5),
+            ..Default::default()
+        }
+        .with_depth_mode(Text3dDepthMode::XRay {
+            occluded_opacity: 0.3,
+        });
+        let handle = cache.material(&texture, &xray, 6, &mut materials);
+        let material = materials.get(&handle).unwrap();
+        assert_eq!(material.alpha_mode, AlphaMode::Blend);
+        assert_eq!(material.params.occluded_opacity, 0.3);
+        assert_ne!(
+            GlyphMaterialKey::from(material),
+            GlyphMaterialKey::from(&GlyphMaterial::new(texture.clone(), Default::default()))
+        );
+
+        let decal = Text3dStyle::default()
+            .with_depth_mode(Text3dDepthMode::SurfaceDecal { depth_bias: 50.0 });
+        let decal_handle = cache.material(&texture, &decal, 6, &mut materials);
+        assert_ne!(handle, decal_handle);
+        assert_eq!(
+            Material::depth_bias(materials.get(&decal_handle).unwrap()),
+            50.0
+        );
+        let lit = Text3dLit::default().material(&decal, texture, 6);
+        assert_eq!(lit.base.depth_bias, 50.0);
+        assert_eq!(lit.base.alpha_mode, AlphaMode::Blend);
+    }
+}
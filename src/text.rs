@@ -2,6 +2,7 @@ use bevy::{app::Plugin, pbr::MaterialPlugin, prelude::*};
 
 use crate::{
     font::FontPlugin,
+    lit::LitGlyphMaterial,
     pipeline::{TextMeshPlugin, TextMeshPluginConfig},
     pipeline_material::{DepthOnlyMaterial, GlyphMaterial},
     shadow_casting::ShadowOnlyMaterialPlugin,
@@ -43,6 +44,7 @@ impl Plugin for Text3dPlugin {
         app.add_plugins(FontPlugin)
             .add_plugins(TextMeshPlugin::with_config(config))
             .add_plugins(MaterialPlugin::<GlyphMaterial>::default())
+            .add_plugins(MaterialPlugin::<LitGlyphMaterial>::default())
             .add_plugins(MaterialPlugin::<DepthOnlyMaterial> {
                 prepass_enabled: true,
                 shadows_enabled: true,